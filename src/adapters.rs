@@ -0,0 +1,200 @@
+//! Derived graphs, as adapters over any `EdgeMapper`.
+//!
+//! Each adapter presents the edges of the graph it wraps, changed on the fly, so that
+//! algorithms run on a reversed, symmetrized or restricted graph without collecting it into
+//! memory or writing new files. Adapters own what they wrap, which may be a reference, and so
+//! compose: `NoSelfLoops(Symmetrized(&graph))` is an undirected view of `graph` without loops.
+//! Each is also a `WeightedEdgeMapper` if what it wraps is, keeping every edge's weight.
+
+use std::ops::ControlFlow;
+
+use crate::graph_iterator::{EdgeMapper, WeightedEdgeMapper};
+
+/// The graph with every edge reversed.
+pub struct Transposed<G>(pub G);
+
+impl<G: EdgeMapper> EdgeMapper for Transposed<G> {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.0.map_edges(|x, y| action(y, x))
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.0.map_edges_until(|x, y| action(y, x))
+    }
+}
+
+impl<G: WeightedEdgeMapper> WeightedEdgeMapper for Transposed<G> {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        self.0
+            .map_weighted_edges(|x, y, weight| action(y, x, weight))
+    }
+}
+
+/// The graph with every edge in both directions, each edge followed by its reverse.
+///
+/// Edges already present in both directions, and self-loops, appear twice each way.
+pub struct Symmetrized<G>(pub G);
+
+impl<G: EdgeMapper> EdgeMapper for Symmetrized<G> {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.0.map_edges(|x, y| {
+            action(x, y);
+            action(y, x);
+        })
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        self.0.map_edges_until(|x, y| {
+            presented += 1;
+            action(x, y)?;
+            presented += 1;
+            action(y, x)
+        });
+        presented
+    }
+}
+
+impl<G: WeightedEdgeMapper> WeightedEdgeMapper for Symmetrized<G> {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        self.0.map_weighted_edges(|x, y, weight| {
+            action(x, y, weight);
+            action(y, x, weight);
+        })
+    }
+}
+
+/// The edges `(x, y)` of the graph for which the predicate holds.
+///
+/// Restricting a graph to a set of vertices is a predicate on both endpoints.
+pub struct Filtered<G, P: Fn(u32, u32) -> bool>(pub G, pub P);
+
+impl<G: EdgeMapper, P: Fn(u32, u32) -> bool> EdgeMapper for Filtered<G, P> {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.0.map_edges(|x, y| {
+            if (self.1)(x, y) {
+                action(x, y);
+            }
+        })
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        self.0.map_edges_until(|x, y| {
+            if (self.1)(x, y) {
+                presented += 1;
+                action(x, y)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        presented
+    }
+}
+
+impl<G: WeightedEdgeMapper, P: Fn(u32, u32) -> bool> WeightedEdgeMapper for Filtered<G, P> {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        self.0.map_weighted_edges(|x, y, weight| {
+            if (self.1)(x, y) {
+                action(x, y, weight);
+            }
+        })
+    }
+}
+
+/// The graph without edges from a vertex to itself.
+pub struct NoSelfLoops<G>(pub G);
+
+impl<G: EdgeMapper> EdgeMapper for NoSelfLoops<G> {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.0.map_edges(|x, y| {
+            if x != y {
+                action(x, y);
+            }
+        })
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        self.0.map_edges_until(|x, y| {
+            if x != y {
+                presented += 1;
+                action(x, y)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        presented
+    }
+}
+
+impl<G: WeightedEdgeMapper> WeightedEdgeMapper for NoSelfLoops<G> {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        self.0.map_weighted_edges(|x, y, weight| {
+            if x != y {
+                action(x, y, weight);
+            }
+        })
+    }
+}
+
+/// The graph with each vertex `v` renamed to `map(v)`.
+///
+/// Names need not be distinct, so merging vertices is a relabelling too. Algorithms need
+/// `nodes` to exceed the largest new name.
+pub struct Relabeled<G, M: Fn(u32) -> u32>(pub G, pub M);
+
+impl<G: EdgeMapper, M: Fn(u32) -> u32> EdgeMapper for Relabeled<G, M> {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.0.map_edges(|x, y| action((self.1)(x), (self.1)(y)))
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.0
+            .map_edges_until(|x, y| action((self.1)(x), (self.1)(y)))
+    }
+}
+
+impl<G: WeightedEdgeMapper, M: Fn(u32) -> u32> WeightedEdgeMapper for Relabeled<G, M> {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        self.0
+            .map_weighted_edges(|x, y, weight| action((self.1)(x), (self.1)(y), weight))
+    }
+}
+
+/// The edges of `graph`, in the order it presents them.
+#[cfg(test)]
+fn edges_of<G: EdgeMapper>(graph: G) -> Vec<(u32, u32)> {
+    let mut edges = Vec::new();
+    graph.map_edges(|x, y| edges.push((x, y)));
+    edges
+}
+
+#[test]
+fn test_adapters() {
+    use crate::algorithms::{bfs, pagerank::pagerank, pagerank::Config};
+
+    let graph = vec![(0, 1), (1, 2), (2, 2), (2, 3), (4, 0)];
+    let transposed = edges_of(Transposed(&graph));
+    assert_eq!(transposed, vec![(1, 0), (2, 1), (2, 2), (3, 2), (0, 4)]);
+    let undirected = edges_of(NoSelfLoops(Symmetrized(&graph)));
+    assert_eq!(undirected.len(), 8);
+    assert!(undirected.contains(&(3, 2)) && !undirected.contains(&(2, 2)));
+    let subset = edges_of(Filtered(&graph, |x, y| x < 3 && y < 3));
+    assert_eq!(subset, vec![(0, 1), (1, 2), (2, 2)]);
+    let merged = edges_of(Relabeled(&graph, |v| v / 2));
+    assert_eq!(merged, vec![(0, 0), (0, 1), (1, 1), (1, 1), (2, 0)]);
+
+    // algorithms run on derived graphs as on the graphs they stand for.
+    let reversed: Vec<(u32, u32)> = graph.iter().map(|&(x, y)| (y, x)).collect();
+    let derived = bfs(&Transposed(&graph), 5, 3, true);
+    assert_eq!(derived.distances, bfs(&reversed, 5, 3, true).distances);
+    let config = Config::default();
+    let derived = pagerank(&Transposed(&graph), 5, &config);
+    assert_eq!(derived.ranks, pagerank(&reversed, 5, &config).ranks);
+
+    let weighted = vec![(0, 1, 0.5), (1, 1, 2.0)];
+    let mut edges = Vec::new();
+    NoSelfLoops(Transposed(&weighted)).map_weighted_edges(|x, y, w| edges.push((x, y, w)));
+    assert_eq!(edges, vec![(1, 0, 0.5)]);
+}
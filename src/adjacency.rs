@@ -0,0 +1,249 @@
+//! Random access to each vertex's neighbors, for algorithms that cannot make do with streaming.
+
+use std::ops::ControlFlow;
+
+use crate::adapters::Transposed;
+use crate::graph_iterator::{EdgeMapper, WeightedEdgeMapper};
+
+/// A graph whose out-neighbors can be looked up by vertex.
+pub trait Adjacency {
+    /// One more than the largest vertex that may have neighbors.
+    fn nodes(&self) -> u32;
+
+    /// The out-neighbors of `vertex`, empty for vertices without out-edges.
+    fn neighbors(&self, vertex: u32) -> &[u32];
+
+    fn out_degree(&self, vertex: u32) -> u32 {
+        self.neighbors(vertex).len() as u32
+    }
+}
+
+/// A graph whose out-edges and their weights can be looked up by vertex.
+pub trait WeightedAdjacency: Adjacency {
+    /// The weights of the out-edges of `vertex`, in the order of `neighbors(vertex)`.
+    fn weights(&self, vertex: u32) -> &[f32];
+}
+
+/// Adjacency lists held in memory in compressed sparse row form.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdjacencyList {
+    /// The neighbors of vertex `v` are `targets[offsets[v] .. offsets[v + 1]]`.
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+}
+
+impl AdjacencyList {
+    /// Collects the edges of `graph`, whose vertices must be less than `nodes`.
+    ///
+    /// Neighbors appear in the order `graph` presents them.
+    pub fn from_edges<G: EdgeMapper>(graph: &G, nodes: u32) -> AdjacencyList {
+        AdjacencyList::build(graph, nodes, false)
+    }
+
+    /// Collects the edges of `graph` in both directions, whose vertices must be less than `nodes`.
+    pub fn symmetric_from_edges<G: EdgeMapper>(graph: &G, nodes: u32) -> AdjacencyList {
+        AdjacencyList::build(graph, nodes, true)
+    }
+
+    fn build<G: EdgeMapper>(graph: &G, nodes: u32, symmetric: bool) -> AdjacencyList {
+        AdjacencyList::build_weighted(&Unweighted(graph), nodes, symmetric, false).0
+    }
+
+    /// Collects edges as `build` does, also collecting their weights if `weighted`.
+    fn build_weighted<G: WeightedEdgeMapper>(
+        graph: &G,
+        nodes: u32,
+        symmetric: bool,
+        weighted: bool,
+    ) -> (AdjacencyList, Vec<f32>) {
+        let mut offsets = vec![0usize; nodes as usize + 1];
+        graph.map_weighted_edges(|x, y, _| {
+            offsets[x as usize + 1] += 1;
+            if symmetric {
+                offsets[y as usize + 1] += 1;
+            }
+        });
+        for vertex in 0..nodes as usize {
+            offsets[vertex + 1] += offsets[vertex];
+        }
+
+        let edges = offsets[nodes as usize];
+        let mut targets = vec![0u32; edges];
+        let mut weights = vec![0f32; if weighted { edges } else { 0 }];
+        let mut cursors = offsets[..nodes as usize].to_vec();
+        let mut place = |x: u32, y: u32, weight: f32| {
+            targets[cursors[x as usize]] = y;
+            if weighted {
+                weights[cursors[x as usize]] = weight;
+            }
+            cursors[x as usize] += 1;
+        };
+        graph.map_weighted_edges(|x, y, weight| {
+            place(x, y, weight);
+            if symmetric {
+                place(y, x, weight);
+            }
+        });
+
+        (AdjacencyList { offsets, targets }, weights)
+    }
+
+    /// Collects the edges of `graph` reversed, whose vertices must be less than `nodes`.
+    pub fn transposed_from_edges<G: EdgeMapper>(graph: &G, nodes: u32) -> AdjacencyList {
+        AdjacencyList::from_edges(&Transposed(graph), nodes)
+    }
+
+    /// The graph with every edge reversed.
+    pub fn transpose(&self) -> AdjacencyList {
+        AdjacencyList::transposed_from_edges(self, self.nodes())
+    }
+
+    /// Sorts each vertex's neighbors and removes repeated ones.
+    pub fn sort_dedup(&mut self) {
+        let mut offsets = Vec::with_capacity(self.offsets.len());
+        let mut targets = Vec::with_capacity(self.targets.len());
+        offsets.push(0);
+        for vertex in 0..self.nodes() {
+            let start = targets.len();
+            targets.extend_from_slice(self.neighbors(vertex));
+            targets[start..].sort_unstable();
+            let mut kept = start;
+            for index in start..targets.len() {
+                if kept == start || targets[kept - 1] != targets[index] {
+                    targets[kept] = targets[index];
+                    kept += 1;
+                }
+            }
+            targets.truncate(kept);
+            offsets.push(targets.len());
+        }
+        self.offsets = offsets;
+        self.targets = targets;
+    }
+
+    pub fn edges(&self) -> usize {
+        self.targets.len()
+    }
+}
+
+impl Adjacency for AdjacencyList {
+    fn nodes(&self) -> u32 {
+        (self.offsets.len() - 1) as u32
+    }
+
+    fn neighbors(&self, vertex: u32) -> &[u32] {
+        match self.offsets.get(vertex as usize + 1) {
+            Some(&end) => &self.targets[self.offsets[vertex as usize]..end],
+            None => &[],
+        }
+    }
+}
+
+impl EdgeMapper for AdjacencyList {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        for vertex in 0..self.nodes() {
+            for &neighbor in self.neighbors(vertex) {
+                action(vertex, neighbor);
+            }
+        }
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        for vertex in 0..self.nodes() {
+            for &neighbor in self.neighbors(vertex) {
+                presented += 1;
+                if action(vertex, neighbor).is_break() {
+                    return presented;
+                }
+            }
+        }
+        presented
+    }
+}
+
+/// Weighted adjacency lists held in memory, the weights parallel to the targets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightedAdjacencyList {
+    list: AdjacencyList,
+    weights: Vec<f32>,
+}
+
+impl WeightedAdjacencyList {
+    /// Collects the weighted edges of `graph`, whose vertices must be less than `nodes`.
+    pub fn from_edges<G: WeightedEdgeMapper>(graph: &G, nodes: u32) -> WeightedAdjacencyList {
+        let (list, weights) = AdjacencyList::build_weighted(graph, nodes, false, true);
+        WeightedAdjacencyList { list, weights }
+    }
+
+    /// Collects the weighted edges of `graph` in both directions.
+    pub fn symmetric_from_edges<G: WeightedEdgeMapper>(
+        graph: &G,
+        nodes: u32,
+    ) -> WeightedAdjacencyList {
+        let (list, weights) = AdjacencyList::build_weighted(graph, nodes, true, true);
+        WeightedAdjacencyList { list, weights }
+    }
+
+    pub fn edges(&self) -> usize {
+        self.list.edges()
+    }
+}
+
+impl Adjacency for WeightedAdjacencyList {
+    fn nodes(&self) -> u32 {
+        self.list.nodes()
+    }
+
+    fn neighbors(&self, vertex: u32) -> &[u32] {
+        self.list.neighbors(vertex)
+    }
+}
+
+impl WeightedAdjacency for WeightedAdjacencyList {
+    fn weights(&self, vertex: u32) -> &[f32] {
+        match self.list.offsets.get(vertex as usize + 1) {
+            Some(&end) => &self.weights[self.list.offsets[vertex as usize]..end],
+            None => &[],
+        }
+    }
+}
+
+impl WeightedEdgeMapper for WeightedAdjacencyList {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        for vertex in 0..self.nodes() {
+            let edges = self.neighbors(vertex).iter().zip(self.weights(vertex));
+            for (&neighbor, &weight) in edges {
+                action(vertex, neighbor, weight);
+            }
+        }
+    }
+}
+
+/// The edges of a graph with unit weights, so that weighted and unweighted lists share code.
+struct Unweighted<'a, G: EdgeMapper>(&'a G);
+
+impl<G: EdgeMapper> WeightedEdgeMapper for Unweighted<'_, G> {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        self.0.map_edges(|x, y| action(x, y, 1.0))
+    }
+}
+
+#[test]
+fn test_adjacency_list() {
+    let graph = AdjacencyList::from_edges(&vec![(0, 2), (2, 1), (0, 1)], 4);
+    assert_eq!((graph.nodes(), graph.edges()), (4, 3));
+    assert_eq!(graph.neighbors(0), &[2, 1]);
+    assert_eq!((graph.out_degree(1), graph.out_degree(3)), (0, 0));
+    assert_eq!(graph.neighbors(7), &[] as &[u32]);
+
+    let transposed = graph.transpose();
+    assert_eq!(transposed.neighbors(1), &[0, 2]);
+    assert_eq!(transposed.transpose().neighbors(0), &[1, 2]);
+
+    let mut symmetric = AdjacencyList::symmetric_from_edges(&vec![(2, 1), (0, 2), (1, 2)], 3);
+    assert_eq!(symmetric.neighbors(2), &[1, 0, 1]);
+    symmetric.sort_dedup();
+    assert_eq!(symmetric.neighbors(2), &[0, 1]);
+    assert_eq!(symmetric.neighbors(1), &[2]);
+}
@@ -0,0 +1,325 @@
+//! Breadth-first search from a start vertex.
+//!
+//! `bfs` streams the edges once per level, which any `EdgeMapper` supports.
+//! `direction_optimizing` instead looks up neighbors, switching between expanding the frontier
+//! top-down and having unvisited vertices search for a parent bottom-up, as Beamer, Asanović
+//! and Patterson describe in "Direction-Optimizing Breadth-First Search".
+
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use crate::adjacency::Adjacency;
+use crate::graph_iterator::EdgeMapper;
+
+/// Switch to bottom-up once the frontier's out-edges exceed the unexplored edges over this.
+pub const ALPHA: u64 = 14;
+/// Switch back to top-down once the frontier shrinks below the vertices over this.
+pub const BETA: u64 = 24;
+
+/// The distance and parent of vertices that were not reached.
+pub const UNREACHED: u32 = u32::MAX;
+
+#[derive(Clone, Debug)]
+pub struct Bfs {
+    /// Each vertex's distance from the start vertex, `UNREACHED` if it was not reached.
+    pub distances: Vec<u32>,
+    /// Each vertex's parent in the BFS tree, `UNREACHED` if it was not reached. The start
+    /// vertex is its own parent.
+    pub parents: Vec<u32>,
+    pub iterations: Vec<Duration>,
+}
+
+impl Bfs {
+    /// The number of vertices reached, including the start vertex.
+    pub fn reached(&self) -> usize {
+        self.distances
+            .iter()
+            .filter(|&&distance| distance != UNREACHED)
+            .count()
+    }
+}
+
+/// Finds the distance of each vertex from `start_vertex`, and a parent one step closer.
+///
+/// Each pass over `graph` expands the frontier, the vertices at the current distance, along
+/// their edges, and passes stop once a frontier is empty. Edges are followed from source to
+/// destination if `directed`, and either way otherwise. The last pass stops part way if every
+/// vertex has been reached.
+pub fn bfs<G: EdgeMapper>(graph: &G, nodes: u32, start_vertex: u32, directed: bool) -> Bfs {
+    search(graph, nodes, start_vertex, None, directed)
+}
+
+/// The distance from `start_vertex` to `target`, if it is reachable, searching as `bfs` does
+/// but stopping as soon as `target` is reached.
+pub fn distance_to<G: EdgeMapper>(
+    graph: &G,
+    nodes: u32,
+    start_vertex: u32,
+    target: u32,
+    directed: bool,
+) -> Option<u32> {
+    assert!(target < nodes, "target must be less than nodes");
+    let distance =
+        search(graph, nodes, start_vertex, Some(target), directed).distances[target as usize];
+    match distance {
+        UNREACHED => None,
+        distance => Some(distance),
+    }
+}
+
+/// Searches as `bfs` describes, stopping once every vertex or `target` is reached.
+fn search<G: EdgeMapper>(
+    graph: &G,
+    nodes: u32,
+    start_vertex: u32,
+    target: Option<u32>,
+    directed: bool,
+) -> Bfs {
+    assert!(start_vertex < nodes, "start vertex must be less than nodes");
+    let timer = Instant::now();
+    let mut iterations = Vec::new();
+
+    let mut distances = vec![UNREACHED; nodes as usize];
+    let mut parents = vec![UNREACHED; nodes as usize];
+    distances[start_vertex as usize] = 0;
+    parents[start_vertex as usize] = start_vertex;
+
+    let found_target =
+        |distances: &[u32]| target.is_some_and(|target| distances[target as usize] != UNREACHED);
+    let mut reached = 1;
+    let mut level = 0;
+    while reached < nodes && !found_target(&distances) {
+        let mut found = 0;
+        graph.map_edges_until(|src, dst| {
+            if distances[src as usize] == level && distances[dst as usize] == UNREACHED {
+                distances[dst as usize] = level + 1;
+                parents[dst as usize] = src;
+                found += 1;
+            }
+            if !directed && distances[dst as usize] == level && distances[src as usize] == UNREACHED
+            {
+                distances[src as usize] = level + 1;
+                parents[src as usize] = dst;
+                found += 1;
+            }
+            // the rest of the pass can find nothing more that is wanted.
+            if reached + found == nodes || found_target(&distances) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        iterations.push(timer.elapsed());
+
+        if found == 0 {
+            break;
+        }
+        reached += found;
+        level += 1;
+    }
+
+    Bfs {
+        distances,
+        parents,
+        iterations,
+    }
+}
+
+/// Finds distances and parents from `start_vertex` as `bfs` does, given the out-neighbors and
+/// in-neighbors of each vertex less than `nodes`.
+///
+/// Levels with small frontiers go top-down, from each frontier vertex to its unvisited
+/// out-neighbors. Levels with large frontiers go bottom-up, from each unvisited vertex to the
+/// first of its in-neighbors in the frontier, which avoids checking most edges. For undirected
+/// traversal pass the symmetric graph as both.
+pub fn direction_optimizing<O: Adjacency, I: Adjacency>(
+    out_edges: &O,
+    in_edges: &I,
+    nodes: u32,
+    start_vertex: u32,
+) -> Bfs {
+    assert!(start_vertex < nodes, "start vertex must be less than nodes");
+    let timer = Instant::now();
+    let mut iterations = Vec::new();
+
+    let mut distances = vec![UNREACHED; nodes as usize];
+    let mut parents = vec![UNREACHED; nodes as usize];
+    distances[start_vertex as usize] = 0;
+    parents[start_vertex as usize] = start_vertex;
+
+    let mut frontier = vec![start_vertex];
+    let mut unexplored: u64 = (0..nodes)
+        .map(|vertex| out_edges.out_degree(vertex) as u64)
+        .sum();
+    let mut bottom_up = false;
+    let mut level = 0;
+
+    while !frontier.is_empty() {
+        let frontier_edges: u64 = frontier
+            .iter()
+            .map(|&vertex| out_edges.out_degree(vertex) as u64)
+            .sum();
+        if !bottom_up && frontier_edges > unexplored / ALPHA {
+            bottom_up = true;
+        } else if bottom_up && (frontier.len() as u64) < nodes as u64 / BETA {
+            bottom_up = false;
+        }
+        unexplored = unexplored.saturating_sub(frontier_edges);
+
+        let mut next = Vec::new();
+        if bottom_up {
+            for vertex in 0..nodes {
+                if distances[vertex as usize] != UNREACHED {
+                    continue;
+                }
+                for &parent in in_edges.neighbors(vertex) {
+                    if distances[parent as usize] == level {
+                        distances[vertex as usize] = level + 1;
+                        parents[vertex as usize] = parent;
+                        next.push(vertex);
+                        break;
+                    }
+                }
+            }
+        } else {
+            for &vertex in frontier.iter() {
+                for &neighbor in out_edges.neighbors(vertex) {
+                    if distances[neighbor as usize] == UNREACHED {
+                        distances[neighbor as usize] = level + 1;
+                        parents[neighbor as usize] = vertex;
+                        next.push(neighbor);
+                    }
+                }
+            }
+        }
+        iterations.push(timer.elapsed());
+
+        frontier = next;
+        level += 1;
+    }
+
+    Bfs {
+        distances,
+        parents,
+        iterations,
+    }
+}
+
+/// Distances by a queue-based BFS over adjacency lists, to check `bfs` against.
+#[cfg(test)]
+fn reference_distances(edges: &[(u32, u32)], nodes: u32, start: u32, directed: bool) -> Vec<u32> {
+    let mut adjacency = vec![Vec::new(); nodes as usize];
+    for &(x, y) in edges.iter() {
+        adjacency[x as usize].push(y);
+        if !directed {
+            adjacency[y as usize].push(x);
+        }
+    }
+    let mut distances = vec![UNREACHED; nodes as usize];
+    let mut queue = std::collections::VecDeque::new();
+    distances[start as usize] = 0;
+    queue.push_back(start);
+    while let Some(vertex) = queue.pop_front() {
+        for &next in adjacency[vertex as usize].iter() {
+            if distances[next as usize] == UNREACHED {
+                distances[next as usize] = distances[vertex as usize] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+#[test]
+fn test_bfs_matches_reference() {
+    use crate::graph_iterator::{NodesEdgesMemMapper, UpperLowerMemMapper};
+
+    let mut edges = Vec::new();
+    NodesEdgesMemMapper::new("./sample_inputs/one").map_edges(|x, y| edges.push((x, y)));
+    let hilbert = UpperLowerMemMapper::new("./sample_inputs/one");
+    // an extra vertex that no edge reaches.
+    let nodes = 9;
+
+    for directed in [false, true] {
+        for start in 0..nodes {
+            let expected = reference_distances(&edges, nodes, start, directed);
+            for target in 0..nodes {
+                let distance = distance_to(&hilbert, nodes, start, target, directed);
+                let reachable = expected[target as usize] != UNREACHED;
+                assert_eq!(
+                    distance,
+                    Some(expected[target as usize]).filter(|_| reachable)
+                );
+            }
+            for result in [
+                bfs(&edges, nodes, start, directed),
+                bfs(&hilbert, nodes, start, directed),
+            ] {
+                assert_eq!(result.distances, expected);
+                for vertex in 0..nodes as usize {
+                    let parent = result.parents[vertex];
+                    if expected[vertex] == 0 {
+                        assert_eq!(parent, start);
+                        continue;
+                    }
+                    if expected[vertex] == UNREACHED {
+                        assert_eq!(parent, UNREACHED);
+                        continue;
+                    }
+                    assert_eq!(expected[parent as usize] + 1, expected[vertex]);
+                    let (x, y) = (parent, vertex as u32);
+                    assert!(
+                        edges.contains(&(x, y)) || (!directed && edges.contains(&(y, x))),
+                        "parent {} of {} is not a neighbour",
+                        parent,
+                        vertex
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_direction_optimizing_matches_reference() {
+    use crate::adjacency::AdjacencyList;
+
+    // a few hubs with many leaves, so that large frontiers go bottom-up.
+    let mut edges = Vec::new();
+    for hub in 0..4u32 {
+        edges.push((hub, (hub + 1) % 4));
+        for leaf in 0..100 {
+            edges.push((hub, 4 + 100 * hub + leaf));
+        }
+    }
+    edges.push((404, 405));
+    edges.push((7, 404));
+    let nodes = 410;
+
+    let forward = AdjacencyList::from_edges(&edges, nodes);
+    let backward = forward.transpose();
+    let symmetric = AdjacencyList::symmetric_from_edges(&edges, nodes);
+    for start in [0, 2, 7, 404, 409] {
+        let directed = direction_optimizing(&forward, &backward, nodes, start);
+        assert_eq!(
+            directed.distances,
+            reference_distances(&edges, nodes, start, true)
+        );
+        let undirected = direction_optimizing(&symmetric, &symmetric, nodes, start);
+        assert_eq!(
+            undirected.distances,
+            reference_distances(&edges, nodes, start, false)
+        );
+        for result in [directed, undirected] {
+            for vertex in 0..nodes as usize {
+                let parent = result.parents[vertex];
+                if parent != UNREACHED && vertex as u32 != start {
+                    assert_eq!(
+                        result.distances[parent as usize] + 1,
+                        result.distances[vertex]
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! k-core decomposition: the coreness of each vertex of an undirected graph.
+//!
+//! A vertex's coreness is the largest `k` such that it belongs to a subgraph in which every
+//! vertex has at least `k` neighbors. The graph's degeneracy is the largest coreness.
+
+use std::time::{Duration, Instant};
+
+use crate::adjacency::Adjacency;
+use crate::graph_iterator::EdgeMapper;
+
+#[derive(Clone, Debug)]
+pub struct Cores {
+    pub coreness: Vec<u32>,
+    /// The largest coreness of any vertex.
+    pub degeneracy: u32,
+    pub iterations: Vec<Duration>,
+}
+
+impl Cores {
+    fn new(coreness: Vec<u32>, iterations: Vec<Duration>) -> Cores {
+        let degeneracy = coreness.iter().copied().max().unwrap_or(0);
+        Cores {
+            coreness,
+            degeneracy,
+            iterations,
+        }
+    }
+}
+
+/// Peels vertices `0 .. nodes` of `graph` in order of degree, as Batagelj and Zaveršnik describe
+/// in "An O(m) Algorithm for Cores Decomposition of Networks".
+///
+/// `graph` must be symmetric and without repeated edges, as the `.binodes`/`.biedges` pair is.
+/// Self-loops are ignored. Vertices are kept in an array sorted by their remaining degree, with
+/// the start of each degree's bucket recorded, so that removing a vertex moves each neighbor
+/// down one bucket in constant time.
+pub fn kcore<A: Adjacency>(graph: &A, nodes: u32) -> Cores {
+    let timer = Instant::now();
+    let n = nodes as usize;
+
+    let mut degrees = vec![0u32; n];
+    for vertex in 0..nodes {
+        let neighbors = graph.neighbors(vertex);
+        degrees[vertex as usize] =
+            neighbors.iter().filter(|&&other| other != vertex).count() as u32;
+    }
+
+    // bins[d] is where vertices of degree d start in `order`, and positions inverts `order`.
+    let max_degree = degrees.iter().copied().max().unwrap_or(0) as usize;
+    let mut bins = vec![0u32; max_degree + 1];
+    for &degree in degrees.iter() {
+        bins[degree as usize] += 1;
+    }
+    let mut start = 0;
+    for bin in bins.iter_mut() {
+        let count = *bin;
+        *bin = start;
+        start += count;
+    }
+    let mut order = vec![0u32; n];
+    let mut positions = vec![0u32; n];
+    {
+        let mut next = bins.clone();
+        for vertex in 0..nodes {
+            let degree = degrees[vertex as usize] as usize;
+            positions[vertex as usize] = next[degree];
+            order[next[degree] as usize] = vertex;
+            next[degree] += 1;
+        }
+    }
+
+    for index in 0..n {
+        let vertex = order[index];
+        let degree = degrees[vertex as usize];
+        for &neighbor in graph.neighbors(vertex) {
+            let neighbor_degree = degrees[neighbor as usize];
+            if neighbor_degree > degree {
+                // swap the neighbor to the front of its bucket, and shrink the bucket past it.
+                let position = positions[neighbor as usize];
+                let front = bins[neighbor_degree as usize];
+                let swapped = order[front as usize];
+                if swapped != neighbor {
+                    order.swap(position as usize, front as usize);
+                    positions[neighbor as usize] = front;
+                    positions[swapped as usize] = position;
+                }
+                bins[neighbor_degree as usize] += 1;
+                degrees[neighbor as usize] -= 1;
+            }
+        }
+    }
+
+    // each vertex's degree was frozen when it was peeled, at its coreness.
+    Cores::new(degrees, vec![timer.elapsed()])
+}
+
+/// Computes coreness with repeated passes over the edges of `graph`, for when neighbors cannot
+/// be looked up.
+///
+/// Each pass counts the neighbors of each remaining vertex among the remaining vertices, and
+/// removes those with at most `k`, whose coreness is `k`. When none is removed `k` grows. Only
+/// sources are counted, so `graph` must present each undirected edge once in each direction, as
+/// the `.binodes`/`.biedges` pair does; telling a reciprocal pair from two parallel edges would
+/// take more than per-vertex state. Self-loops are ignored.
+pub fn kcore_streaming<G: EdgeMapper>(graph: &G, nodes: u32) -> Cores {
+    let timer = Instant::now();
+    let mut iterations = Vec::new();
+
+    const REMAINING: u32 = u32::MAX;
+    let mut coreness = vec![REMAINING; nodes as usize];
+    let mut degrees = vec![0u32; nodes as usize];
+    let mut remaining = nodes as usize;
+    let mut k = 0;
+
+    while remaining > 0 {
+        for degree in degrees.iter_mut() {
+            *degree = 0;
+        }
+        graph.map_edges(|x, y| {
+            if x != y && coreness[x as usize] == REMAINING && coreness[y as usize] == REMAINING {
+                degrees[x as usize] += 1;
+            }
+        });
+        iterations.push(timer.elapsed());
+
+        // peel at this k until nothing is removed, which needs no new pass.
+        loop {
+            let mut removed = 0;
+            for (vertex, core) in coreness.iter_mut().enumerate() {
+                if *core == REMAINING && degrees[vertex] <= k {
+                    *core = k;
+                    removed += 1;
+                }
+            }
+            remaining -= removed;
+            if removed > 0 || remaining == 0 {
+                break;
+            }
+            k += 1;
+        }
+    }
+
+    Cores::new(coreness, iterations)
+}
+
+#[test]
+fn test_kcore() {
+    use crate::adjacency::AdjacencyList;
+
+    // a 4-clique on 0..4, a triangle 4, 5, 6 hanging off 3, a pendant 7, and isolated 8.
+    let mut edges = vec![(3, 4), (4, 5), (5, 6), (6, 4), (6, 7), (5, 5)];
+    for x in 0..4 {
+        for y in x + 1..4 {
+            edges.push((x, y));
+        }
+    }
+    let expected = vec![3, 3, 3, 3, 2, 2, 2, 1, 0];
+
+    let mut graph = AdjacencyList::symmetric_from_edges(&edges, 9);
+    graph.sort_dedup();
+    let cores = kcore(&graph, 9);
+    assert_eq!((cores.coreness, cores.degeneracy), (expected.clone(), 3));
+
+    let streamed = kcore_streaming(&graph, 9);
+    assert_eq!((streamed.coreness, streamed.degeneracy), (expected, 3));
+}
+
+#[test]
+fn test_kcore_reciprocal() {
+    use crate::adjacency::AdjacencyList;
+
+    // the triangle 0, 1, 2 with each edge in both directions, as `.biedges` holds it.
+    let edges = vec![(0, 1), (1, 0), (1, 2), (2, 1), (2, 0), (0, 2)];
+    let mut graph = AdjacencyList::symmetric_from_edges(&edges, 3);
+    graph.sort_dedup();
+    let cores = kcore(&graph, 3);
+    assert_eq!((cores.coreness, cores.degeneracy), (vec![2, 2, 2], 2));
+
+    let streamed = kcore_streaming(&edges, 3);
+    assert_eq!((streamed.coreness, streamed.degeneracy), (vec![2, 2, 2], 2));
+}
@@ -0,0 +1,68 @@
+//! Connected components by label propagation.
+
+use std::time::{Duration, Instant};
+
+use crate::graph_iterator::EdgeMapper;
+
+#[derive(Clone, Debug)]
+pub struct Components {
+    /// The number of connected components.
+    pub components: u32,
+    /// The smallest vertex identifier in each vertex's component.
+    pub labels: Vec<u32>,
+    pub iterations: Vec<Duration>,
+}
+
+/// Propagates the smallest label along edges, in either direction, until none changes.
+pub fn label_propagation<G: EdgeMapper>(graph: &G, nodes: u32) -> Components {
+    let timer = Instant::now();
+    let mut iterations = Vec::new();
+
+    let mut label: Vec<u32> = (0..nodes).collect();
+    let mut new_sum: u64 = if nodes % 2 == 0 {
+        (nodes as u64 >> 1) * (nodes as u64).saturating_sub(1)
+    } else {
+        (nodes as u64) * ((nodes as u64).saturating_sub(1) >> 1)
+    };
+    let mut old_sum: u64 = new_sum + 1;
+    let mut roots = nodes;
+
+    while new_sum < old_sum {
+        old_sum = new_sum;
+        graph.map_edges(
+            |src, dst| match label[src as usize].cmp(&label[dst as usize]) {
+                std::cmp::Ordering::Less => {
+                    if label[dst as usize] == dst {
+                        roots -= 1;
+                    }
+                    new_sum += label[src as usize] as u64;
+                    new_sum -= label[dst as usize] as u64;
+                    label[dst as usize] = label[src as usize];
+                }
+                std::cmp::Ordering::Greater => {
+                    if label[src as usize] == src {
+                        roots -= 1;
+                    }
+                    new_sum += label[dst as usize] as u64;
+                    new_sum -= label[src as usize] as u64;
+                    label[src as usize] = label[dst as usize];
+                }
+                std::cmp::Ordering::Equal => {}
+            },
+        );
+        iterations.push(timer.elapsed());
+    }
+    Components {
+        components: roots,
+        labels: label,
+        iterations,
+    }
+}
+
+#[test]
+fn test_label_propagation() {
+    let result = label_propagation(&vec![(3, 1), (1, 0), (4, 5)], 7);
+    assert_eq!(result.components, 4);
+    assert_eq!(result.labels, vec![0, 0, 2, 0, 4, 4, 6]);
+    assert_eq!(label_propagation(&Vec::new(), 0).components, 0);
+}
@@ -0,0 +1,26 @@
+//! Graph algorithms, generic over any `EdgeMapper`.
+//!
+//! Each algorithm returns a result struct holding its per-vertex output alongside the time
+//! elapsed by the end of each iteration, measured from when the algorithm began.
+
+pub mod bfs;
+pub mod kcore;
+pub mod label_propagation;
+pub mod pagerank;
+pub mod parallel;
+pub mod scc;
+pub mod spanning_forest;
+pub mod sssp;
+pub mod triangles;
+pub mod union_find;
+
+pub use self::bfs::{bfs, direction_optimizing, distance_to, Bfs};
+pub use self::kcore::{kcore, kcore_streaming, Cores};
+pub use self::label_propagation::{label_propagation, Components};
+pub use self::pagerank::{pagerank, PageRank};
+pub use self::parallel::{parallel_pagerank, parallel_union_find};
+pub use self::scc::{strongly_connected, StronglyConnected};
+pub use self::spanning_forest::{boruvka, kruskal, SpanningForest};
+pub use self::sssp::{bellman_ford, delta_stepping, dijkstra, ShortestPaths};
+pub use self::triangles::{triangles, Triangles};
+pub use self::union_find::{union_find, UnionFind};
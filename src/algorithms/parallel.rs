@@ -0,0 +1,234 @@
+//! Multi-threaded PageRank and union-find, for comparison with the single-threaded versions.
+//!
+//! Each thread maps one share of the edges from `ParallelEdgeMapper::partition`. PageRank is
+//! partitioned: each thread accumulates into its own rank vector, and the vectors are summed
+//! by ranges of vertices afterwards. Union-find is atomic: all threads link the same forest,
+//! with compare-and-swap.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Instant;
+
+use crate::algorithms::pagerank::PageRank;
+use crate::algorithms::union_find::UnionFind;
+use crate::graph_iterator::{EdgeMapper, ParallelEdgeMapper};
+
+/// Runs `iterations` of PageRank over vertices `0 .. nodes` with damping `alpha`, using
+/// `threads` threads.
+///
+/// The ranks are those of `pagerank` with the default uniform handling of vertices without
+/// out-edges, up to the order in which floating point contributions are added.
+pub fn parallel_pagerank<P: ParallelEdgeMapper + ?Sized>(
+    graph: &P,
+    nodes: u32,
+    iterations: usize,
+    alpha: f32,
+    threads: usize,
+) -> PageRank {
+    let timer = Instant::now();
+    let parts = graph.partition(threads);
+    let mut partials = vec![vec![0f32; nodes as usize]; parts.len()];
+    let mut timings = Vec::with_capacity(iterations);
+
+    let mut src = vec![0f32; nodes as usize];
+    let mut dst = vec![0f32; nodes as usize];
+    let mut deg = vec![0f32; nodes as usize];
+
+    map_parts(&parts, &mut partials, |part, partial| {
+        part.map_edges(|x, _| partial[x as usize] += 1f32)
+    });
+    sum_parts(&partials, &mut deg, 0f32, threads);
+
+    for _iteration in 0..iterations {
+        let mut dangling = 0f64;
+        for node in 0..nodes as usize {
+            if deg[node] > 0f32 {
+                src[node] = alpha * dst[node] / deg[node];
+            } else {
+                src[node] = 0f32;
+                dangling += (alpha * dst[node]) as f64;
+            }
+        }
+
+        let src = &src;
+        map_parts(&parts, &mut partials, |part, partial| {
+            partial.iter_mut().for_each(|rank| *rank = 0f32);
+            // UNSAFE:
+            part.map_edges(|x, y| unsafe {
+                *partial.get_unchecked_mut(y as usize) += *src.get_unchecked(x as usize);
+            });
+        });
+        let share = (dangling / nodes as f64) as f32;
+        sum_parts(&partials, &mut dst, (1f32 - alpha) + share, threads);
+        timings.push(timer.elapsed());
+    }
+
+    PageRank {
+        ranks: dst,
+        iterations: timings,
+        delta: None,
+        converged: false,
+    }
+}
+
+/// Runs `action` on each part and its own partial vector, a thread each.
+fn map_parts<T: EdgeMapper + Sync>(
+    parts: &[T],
+    partials: &mut [Vec<f32>],
+    action: impl Fn(&T, &mut Vec<f32>) + Sync,
+) {
+    let action = &action;
+    thread::scope(|scope| {
+        for (part, partial) in parts.iter().zip(partials.iter_mut()) {
+            scope.spawn(move || action(part, partial));
+        }
+    });
+}
+
+/// Sets `total` to `base` plus the sum of `partials`, with `threads` threads each summing a
+/// range of vertices.
+fn sum_parts(partials: &[Vec<f32>], total: &mut [f32], base: f32, threads: usize) {
+    let size = total.len().div_ceil(threads.max(1)).max(1);
+    thread::scope(|scope| {
+        for (index, range) in total.chunks_mut(size).enumerate() {
+            scope.spawn(move || {
+                let offset = index * size;
+                for (node, sum) in range.iter_mut().enumerate() {
+                    *sum = base;
+                    for partial in partials.iter() {
+                        *sum += partial[offset + node];
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Unions the endpoints of each edge, using `threads` threads that share one forest.
+///
+/// Roots are linked under smaller roots with compare-and-swap, retrying if another thread got
+/// there first, and paths are halved as they are followed. As each vertex's parent is never
+/// larger than it, links cannot form a cycle however threads interleave, and so relaxed
+/// atomics suffice. The trees differ from `union_find`'s, but the components are the same.
+pub fn parallel_union_find<P: ParallelEdgeMapper + ?Sized>(
+    graph: &P,
+    nodes: u32,
+    threads: usize,
+) -> UnionFind {
+    let timer = Instant::now();
+    let roots: Vec<AtomicU32> = (0..nodes).map(AtomicU32::new).collect();
+
+    let roots_ref = &roots;
+    thread::scope(|scope| {
+        for part in graph.partition(threads) {
+            scope.spawn(move || part.map_edges(|x, y| union(roots_ref, x, y)));
+        }
+    });
+
+    let roots: Vec<u32> = roots.into_iter().map(AtomicU32::into_inner).collect();
+    let non_roots = roots
+        .iter()
+        .enumerate()
+        .filter(|&(vertex, &root)| vertex as u32 != root)
+        .count() as u32;
+    UnionFind {
+        roots,
+        non_roots,
+        iterations: vec![timer.elapsed()],
+    }
+}
+
+fn find(roots: &[AtomicU32], mut vertex: u32) -> u32 {
+    loop {
+        let parent = roots[vertex as usize].load(Ordering::Relaxed);
+        if parent == vertex {
+            return vertex;
+        }
+        let grandparent = roots[parent as usize].load(Ordering::Relaxed);
+        if grandparent != parent {
+            let _ = roots[vertex as usize].compare_exchange_weak(
+                parent,
+                grandparent,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+        }
+        vertex = grandparent;
+    }
+}
+
+fn union(roots: &[AtomicU32], mut x: u32, mut y: u32) {
+    loop {
+        x = find(roots, x);
+        y = find(roots, y);
+        if x == y {
+            return;
+        }
+        let (child, parent) = if x > y { (x, y) } else { (y, x) };
+        let linked = roots[child as usize].compare_exchange(
+            child,
+            parent,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+        if linked.is_ok() {
+            return;
+        }
+    }
+}
+
+#[test]
+fn test_parallel_matches_sequential() {
+    use crate::algorithms::pagerank::{pagerank, Config};
+    use crate::algorithms::union_find;
+    use crate::graph_iterator::{NodesEdgesMemMapper, UpperLowerMemMapper};
+
+    // a few cycles with chords and some isolated vertices.
+    let nodes = 1000;
+    let mut edges = Vec::new();
+    for x in 0..900u32 {
+        edges.push((x, (x + 1) % 300 + 300 * (x / 300)));
+        edges.push((x, (x * 7) % 300 + 300 * (x / 300)));
+    }
+    let config = Config {
+        iterations: 10,
+        ..Config::default()
+    };
+    let expected = pagerank(&edges, nodes, &config).ranks;
+    let components = union_find(&edges, nodes).labels();
+    for threads in [1, 3, 8] {
+        let parts = edges.partition(threads);
+        assert!(parts.len() <= threads);
+        assert_eq!(parts.concat(), edges);
+
+        let ranks = parallel_pagerank(&edges, nodes, 10, 0.85, threads).ranks;
+        for (rank, expected) in ranks.iter().zip(expected.iter()) {
+            assert!((rank - expected).abs() < 1e-4, "{} != {}", rank, expected);
+        }
+        assert_eq!(
+            parallel_union_find(&edges, nodes, threads).labels(),
+            components
+        );
+    }
+
+    let vertex = NodesEdgesMemMapper::new("./sample_inputs/one");
+    let hilbert = UpperLowerMemMapper::new("./sample_inputs/one");
+    let mut expected = Vec::new();
+    vertex.map_edges(|x, y| expected.push((x, y)));
+    expected.sort();
+    for threads in [1, 2, 5, 100] {
+        let mut split = Vec::new();
+        for part in vertex.partition(threads) {
+            part.map_edges(|x, y| split.push((x, y)));
+        }
+        assert_eq!(split, expected);
+        let mut split = Vec::new();
+        for part in hilbert.partition(threads) {
+            part.map_edges(|x, y| split.push((x, y)));
+        }
+        split.sort();
+        assert_eq!(split, expected);
+        let labels = parallel_union_find(&hilbert, 8, threads).labels();
+        assert_eq!(labels, union_find(&expected, 8).labels());
+    }
+}
@@ -0,0 +1,132 @@
+//! Strongly connected components of a directed graph, by Tarjan's algorithm.
+
+use std::time::{Duration, Instant};
+
+use crate::adjacency::Adjacency;
+
+#[derive(Clone, Debug)]
+pub struct StronglyConnected {
+    /// The number of strongly connected components.
+    pub components: u32,
+    /// The smallest vertex identifier in each vertex's component.
+    pub labels: Vec<u32>,
+    /// The size of the largest component.
+    pub largest: u32,
+    pub iterations: Vec<Duration>,
+}
+
+const UNVISITED: u32 = u32::MAX;
+
+/// Finds the strongly connected components among vertices `0 .. nodes` of `graph`.
+///
+/// This is Tarjan's algorithm with an explicit stack in place of recursion, so that long paths
+/// do not overflow the call stack. It visits each vertex and edge once.
+pub fn strongly_connected<A: Adjacency>(graph: &A, nodes: u32) -> StronglyConnected {
+    let timer = Instant::now();
+
+    // the order in which vertices were discovered, and the earliest discovered vertex each can
+    // reach through its subtree and a back edge.
+    let mut index = vec![UNVISITED; nodes as usize];
+    let mut lowlink = vec![0u32; nodes as usize];
+    let mut on_stack = vec![false; nodes as usize];
+    let mut labels = vec![0u32; nodes as usize];
+
+    let mut stack: Vec<u32> = Vec::new();
+    // vertices being visited, with the position of the next neighbor to consider.
+    let mut calls: Vec<(u32, usize)> = Vec::new();
+    let mut discovered = 0u32;
+    let mut components = 0u32;
+    let mut largest = 0u32;
+
+    for root in 0..nodes {
+        if index[root as usize] != UNVISITED {
+            continue;
+        }
+        calls.push((root, 0));
+        while let Some(&mut (vertex, ref mut next)) = calls.last_mut() {
+            if *next == 0 && index[vertex as usize] == UNVISITED {
+                index[vertex as usize] = discovered;
+                lowlink[vertex as usize] = discovered;
+                discovered += 1;
+                stack.push(vertex);
+                on_stack[vertex as usize] = true;
+            }
+
+            let neighbors = graph.neighbors(vertex);
+            if let Some(&neighbor) = neighbors.get(*next) {
+                *next += 1;
+                if index[neighbor as usize] == UNVISITED {
+                    calls.push((neighbor, 0));
+                } else if on_stack[neighbor as usize] {
+                    let low = lowlink[vertex as usize].min(index[neighbor as usize]);
+                    lowlink[vertex as usize] = low;
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                let low = lowlink[parent as usize].min(lowlink[vertex as usize]);
+                lowlink[parent as usize] = low;
+            }
+            if lowlink[vertex as usize] == index[vertex as usize] {
+                // `vertex` roots a component, which is everything above it on the stack.
+                let position = stack.iter().rposition(|&member| member == vertex).unwrap();
+                let members = &stack[position..];
+                let least = *members.iter().min().unwrap();
+                for &member in members.iter() {
+                    on_stack[member as usize] = false;
+                    labels[member as usize] = least;
+                }
+                largest = largest.max(members.len() as u32);
+                components += 1;
+                stack.truncate(position);
+            }
+        }
+    }
+
+    StronglyConnected {
+        components,
+        labels,
+        largest,
+        iterations: vec![timer.elapsed()],
+    }
+}
+
+#[test]
+fn test_strongly_connected() {
+    use crate::adjacency::AdjacencyList;
+    use crate::algorithms::bfs::{bfs, UNREACHED};
+
+    // two cycles joined one way, a self-loop, a chain, and an isolated vertex.
+    let edges = vec![
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (2, 3),
+        (3, 4),
+        (4, 5),
+        (5, 3),
+        (6, 6),
+        (6, 7),
+        (7, 8),
+    ];
+    let nodes = 10;
+    let result = strongly_connected(&AdjacencyList::from_edges(&edges, nodes), nodes);
+    assert_eq!(result.labels, vec![0, 0, 0, 3, 3, 3, 6, 7, 8, 9]);
+    assert_eq!((result.components, result.largest), (6, 3));
+
+    // vertices share a component exactly when each reaches the other.
+    let reaches: Vec<Vec<bool>> = (0..nodes)
+        .map(|start| {
+            let distances = bfs(&edges, nodes, start, true).distances;
+            distances.iter().map(|&d| d != UNREACHED).collect()
+        })
+        .collect();
+    for (x, &label_x) in result.labels.iter().enumerate() {
+        for (y, &label_y) in result.labels.iter().enumerate() {
+            let mutual = reaches[x][y] && reaches[y][x];
+            assert_eq!(label_x == label_y, mutual);
+        }
+    }
+}
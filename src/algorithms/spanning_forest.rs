@@ -0,0 +1,149 @@
+//! Minimum spanning forests of weighted graphs, treating edges as undirected.
+//!
+//! `kruskal` collects and sorts the edges, and so holds them all in memory. `boruvka` instead
+//! streams the edges once per round, keeping only per-vertex state.
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::algorithms::union_find::DisjointSets;
+use crate::graph_iterator::WeightedEdgeMapper;
+
+#[derive(Clone, Debug)]
+pub struct SpanningForest {
+    /// The edges of the forest, as `graph` presented them.
+    pub edges: Vec<(u32, u32, f32)>,
+    /// The total weight of the forest's edges.
+    pub weight: f64,
+    pub iterations: Vec<Duration>,
+}
+
+impl SpanningForest {
+    fn new() -> SpanningForest {
+        SpanningForest {
+            edges: Vec::new(),
+            weight: 0.0,
+            iterations: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, x: u32, y: u32, weight: f32) {
+        self.edges.push((x, y, weight));
+        self.weight += weight as f64;
+    }
+}
+
+/// Orders edges by weight, breaking ties by their endpoints, so that every round of `boruvka`
+/// agrees on which of two equally heavy edges is lighter.
+fn lighter(a: (u32, u32, f32), b: (u32, u32, f32)) -> Ordering {
+    let key = |(x, y, _): (u32, u32, f32)| (x.min(y), x.max(y));
+    a.2.total_cmp(&b.2).then(key(a).cmp(&key(b)))
+}
+
+/// Finds a minimum spanning forest of vertices `0 .. nodes` by Kruskal's algorithm.
+///
+/// The edges are sorted by weight, and each joining two trees of the forest so far is added,
+/// as `union_find` would union its endpoints.
+pub fn kruskal<G: WeightedEdgeMapper>(graph: &G, nodes: u32) -> SpanningForest {
+    let timer = Instant::now();
+    let mut forest = SpanningForest::new();
+
+    let mut edges = Vec::new();
+    graph.map_weighted_edges(|x, y, weight| {
+        if x != y {
+            edges.push((x, y, weight));
+        }
+    });
+    edges.sort_unstable_by(|&a, &b| lighter(a, b));
+    forest.iterations.push(timer.elapsed());
+
+    let mut sets = DisjointSets::new(nodes);
+    for (x, y, weight) in edges {
+        if sets.union(x, y) {
+            forest.add(x, y, weight);
+        }
+    }
+    forest.iterations.push(timer.elapsed());
+    forest
+}
+
+/// Finds a minimum spanning forest of vertices `0 .. nodes` by Borůvka's algorithm.
+///
+/// Each pass over `graph` finds the lightest edge leaving each tree of the forest so far, and
+/// adds them all, at least halving the number of trees that have edges leaving them. Passes
+/// stop once no edge leaves any tree.
+pub fn boruvka<G: WeightedEdgeMapper>(graph: &G, nodes: u32) -> SpanningForest {
+    let timer = Instant::now();
+    let mut forest = SpanningForest::new();
+
+    let mut sets = DisjointSets::new(nodes);
+    let mut trees = vec![0u32; nodes as usize];
+    let mut lightest: Vec<Option<(u32, u32, f32)>> = vec![None; nodes as usize];
+    loop {
+        for (vertex, tree) in trees.iter_mut().enumerate() {
+            *tree = sets.find(vertex as u32);
+        }
+        graph.map_weighted_edges(|x, y, weight| {
+            let (tree_x, tree_y) = (trees[x as usize], trees[y as usize]);
+            if tree_x != tree_y {
+                for tree in [tree_x, tree_y] {
+                    let best = &mut lightest[tree as usize];
+                    if best.is_none_or(|best| lighter((x, y, weight), best).is_lt()) {
+                        *best = Some((x, y, weight));
+                    }
+                }
+            }
+        });
+        forest.iterations.push(timer.elapsed());
+
+        let mut added = false;
+        for best in lightest.iter_mut() {
+            if let Some((x, y, weight)) = best.take() {
+                // two trees may pick the same edge, which joins them only once.
+                if sets.union(x, y) {
+                    forest.add(x, y, weight);
+                    added = true;
+                }
+            }
+        }
+        if !added {
+            break;
+        }
+    }
+    forest
+}
+
+#[test]
+fn test_spanning_forest() {
+    use crate::algorithms::union_find;
+
+    // a square with a heavy diagonal, equal weights around a triangle, and a separate edge.
+    let edges = vec![
+        (0, 1, 1.0),
+        (1, 2, 2.0),
+        (2, 3, 1.0),
+        (3, 0, 3.0),
+        (0, 2, 5.0),
+        (4, 5, 2.0),
+        (5, 6, 2.0),
+        (6, 4, 2.0),
+        (7, 8, -1.0),
+        (8, 8, -9.0),
+    ];
+    let nodes = 10;
+    let unweighted: Vec<(u32, u32)> = edges.iter().map(|&(x, y, _)| (x, y)).collect();
+    let components = union_find(&unweighted, nodes).components();
+
+    let kruskal = kruskal(&edges, nodes);
+    let boruvka = boruvka(&edges, nodes);
+    for forest in [&kruskal, &boruvka] {
+        assert_eq!(forest.weight, 7.0);
+        assert_eq!(forest.edges.len() as u32, nodes - components);
+        assert!(forest.edges.iter().all(|edge| edges.contains(edge)));
+        let tree: Vec<(u32, u32)> = forest.edges.iter().map(|&(x, y, _)| (x, y)).collect();
+        assert_eq!(union_find(&tree, nodes).components(), components);
+    }
+    let mut sorted = boruvka.edges.clone();
+    sorted.sort_by(|&a, &b| lighter(a, b));
+    assert_eq!(kruskal.edges, sorted);
+}
@@ -0,0 +1,281 @@
+//! Single-source shortest paths over weighted edges.
+//!
+//! `bellman_ford` streams the edges once per round, which any `WeightedEdgeMapper` supports and
+//! which tolerates negative weights. `dijkstra` instead looks up out-edges, and settles each
+//! vertex once in order of distance, which needs weights that are not negative. `delta_stepping`
+//! also looks up out-edges, but settles vertices a band of distances at a time, relaxing the
+//! edges out of a band together rather than one vertex after another.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use crate::adjacency::WeightedAdjacency;
+use crate::algorithms::bfs::UNREACHED;
+use crate::graph_iterator::WeightedEdgeMapper;
+
+#[derive(Clone, Debug)]
+pub struct ShortestPaths {
+    /// Each vertex's distance from the source, infinite if it was not reached.
+    pub distances: Vec<f32>,
+    /// The vertex before each on a shortest path, `UNREACHED` if it was not reached. The
+    /// source is its own parent.
+    pub parents: Vec<u32>,
+    /// Whether a cycle of negative weight is reachable from the source, in which case
+    /// distances are not well defined.
+    pub negative_cycle: bool,
+    pub iterations: Vec<Duration>,
+}
+
+impl ShortestPaths {
+    fn new(nodes: u32, source: u32) -> ShortestPaths {
+        let mut distances = vec![f32::INFINITY; nodes as usize];
+        let mut parents = vec![UNREACHED; nodes as usize];
+        distances[source as usize] = 0.0;
+        parents[source as usize] = source;
+        ShortestPaths {
+            distances,
+            parents,
+            negative_cycle: false,
+            iterations: Vec::new(),
+        }
+    }
+
+    /// The number of vertices reached, including the source.
+    pub fn reached(&self) -> usize {
+        self.parents
+            .iter()
+            .filter(|&&parent| parent != UNREACHED)
+            .count()
+    }
+}
+
+/// Finds the distance of each vertex from `source` by relaxing every edge in each pass over
+/// `graph`, until a pass improves nothing.
+///
+/// Edges are followed from source to destination if `directed`, and either way otherwise.
+/// Improvements apply at once, so a pass may carry them along several edges. A path that is
+/// still improving after `nodes` passes must go round a negative cycle, and ends the search.
+pub fn bellman_ford<G: WeightedEdgeMapper>(
+    graph: &G,
+    nodes: u32,
+    source: u32,
+    directed: bool,
+) -> ShortestPaths {
+    assert!(source < nodes, "source must be less than nodes");
+    let timer = Instant::now();
+    let mut paths = ShortestPaths::new(nodes, source);
+    let distances = &mut paths.distances;
+    let parents = &mut paths.parents;
+
+    let mut passes = 0;
+    loop {
+        let mut improved = false;
+        graph.map_weighted_edges(|x, y, weight| {
+            let through_x = distances[x as usize] + weight;
+            if through_x < distances[y as usize] {
+                distances[y as usize] = through_x;
+                parents[y as usize] = x;
+                improved = true;
+            }
+            if !directed {
+                let through_y = distances[y as usize] + weight;
+                if through_y < distances[x as usize] {
+                    distances[x as usize] = through_y;
+                    parents[x as usize] = y;
+                    improved = true;
+                }
+            }
+        });
+        paths.iterations.push(timer.elapsed());
+        passes += 1;
+
+        if !improved {
+            break;
+        }
+        if passes >= nodes {
+            paths.negative_cycle = true;
+            break;
+        }
+    }
+    paths
+}
+
+/// Finds the distance of each vertex less than `nodes` from `source`, by Dijkstra's algorithm
+/// with a binary heap.
+///
+/// Weights must not be negative. Vertices are settled in order of distance, each relaxing its
+/// out-edges once; a vertex may be queued more than once, and stale entries are skipped.
+pub fn dijkstra<A: WeightedAdjacency>(graph: &A, nodes: u32, source: u32) -> ShortestPaths {
+    assert!(source < nodes, "source must be less than nodes");
+    let timer = Instant::now();
+    let mut paths = ShortestPaths::new(nodes, source);
+    let distances = &mut paths.distances;
+    let parents = &mut paths.parents;
+
+    let mut settled = vec![false; nodes as usize];
+    let mut heap = BinaryHeap::new();
+    heap.push(Queued(0.0, source));
+    while let Some(Queued(distance, vertex)) = heap.pop() {
+        if settled[vertex as usize] {
+            continue;
+        }
+        settled[vertex as usize] = true;
+        let edges = graph.neighbors(vertex).iter().zip(graph.weights(vertex));
+        for (&neighbor, &weight) in edges {
+            debug_assert!(weight >= 0.0, "negative weight {}", weight);
+            let through = distance + weight;
+            if through < distances[neighbor as usize] {
+                distances[neighbor as usize] = through;
+                parents[neighbor as usize] = vertex;
+                heap.push(Queued(through, neighbor));
+            }
+        }
+    }
+    paths.iterations.push(timer.elapsed());
+    paths
+}
+
+/// Finds the distance of each vertex less than `nodes` from `source`, by delta-stepping.
+///
+/// Weights must not be negative. Vertices are kept in buckets of tentative distances `delta`
+/// wide, and the nearest non-empty bucket is settled at a time: its vertices relax their light
+/// edges, those of weight at most `delta`, until no vertex re-enters the bucket, and then the
+/// heavy edges of every vertex it held. A `delta` smaller than every weight behaves like
+/// Dijkstra's algorithm, and one larger than every distance like Bellman-Ford. Buckets are held
+/// up to the largest distance, so `delta` should not be tiny next to the path lengths.
+pub fn delta_stepping<A: WeightedAdjacency>(
+    graph: &A,
+    nodes: u32,
+    source: u32,
+    delta: f32,
+) -> ShortestPaths {
+    assert!(source < nodes, "source must be less than nodes");
+    assert!(delta > 0.0, "delta must be positive");
+    let timer = Instant::now();
+    let mut paths = ShortestPaths::new(nodes, source);
+    let bucket = |distance: f32| (distance / delta) as usize;
+
+    let mut buckets: Vec<Vec<u32>> = vec![vec![source]];
+    let mut current = 0;
+    while current < buckets.len() {
+        let mut settled = Vec::new();
+        while !buckets[current].is_empty() {
+            for vertex in mem::take(&mut buckets[current]) {
+                // skip vertices that have since moved to a nearer bucket, and been settled there.
+                if bucket(paths.distances[vertex as usize]) == current {
+                    settled.push(vertex);
+                    relax(graph, &mut paths, &mut buckets, delta, vertex, true);
+                }
+            }
+        }
+        for &vertex in settled.iter() {
+            relax(graph, &mut paths, &mut buckets, delta, vertex, false);
+        }
+        if !settled.is_empty() {
+            paths.iterations.push(timer.elapsed());
+        }
+        current += 1;
+    }
+    paths
+}
+
+/// Relaxes the light or heavy out-edges of `vertex`, moving improved neighbors to the buckets
+/// of their new distances.
+fn relax<A: WeightedAdjacency>(
+    graph: &A,
+    paths: &mut ShortestPaths,
+    buckets: &mut Vec<Vec<u32>>,
+    delta: f32,
+    vertex: u32,
+    light: bool,
+) {
+    let distance = paths.distances[vertex as usize];
+    let edges = graph.neighbors(vertex).iter().zip(graph.weights(vertex));
+    for (&neighbor, &weight) in edges {
+        debug_assert!(weight >= 0.0, "negative weight {}", weight);
+        if (weight <= delta) != light {
+            continue;
+        }
+        let through = distance + weight;
+        if through < paths.distances[neighbor as usize] {
+            paths.distances[neighbor as usize] = through;
+            paths.parents[neighbor as usize] = vertex;
+            let index = (through / delta) as usize;
+            if index >= buckets.len() {
+                buckets.resize(index + 1, Vec::new());
+            }
+            buckets[index].push(neighbor);
+        }
+    }
+}
+
+/// A vertex queued at a distance, ordered so that `BinaryHeap` pops the nearest first.
+struct Queued(f32, u32);
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Queued) -> Ordering {
+        other.0.total_cmp(&self.0).then(other.1.cmp(&self.1))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Queued) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Queued) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+#[test]
+fn test_shortest_paths() {
+    use crate::adjacency::WeightedAdjacencyList;
+
+    // a direct heavy edge and a lighter detour, a cheap edge back, and an unreached vertex.
+    let edges = vec![
+        (0, 1, 4.0),
+        (0, 2, 1.0),
+        (2, 1, 2.0),
+        (1, 3, 1.0),
+        (2, 3, 5.0),
+        (3, 0, 0.5),
+        (4, 3, 1.0),
+    ];
+    let nodes = 6;
+    let infinity = f32::INFINITY;
+
+    let streamed = bellman_ford(&edges, nodes, 0, true);
+    let lists = WeightedAdjacencyList::from_edges(&edges, nodes);
+    let indexed = dijkstra(&lists, nodes, 0);
+    let bucketed: Vec<ShortestPaths> = [0.5, 1.0, 2.5, 100.0]
+        .iter()
+        .map(|&delta| delta_stepping(&lists, nodes, 0, delta))
+        .collect();
+    for paths in [&streamed, &indexed].into_iter().chain(bucketed.iter()) {
+        assert_eq!(
+            paths.distances,
+            vec![0.0, 3.0, 1.0, 4.0, infinity, infinity]
+        );
+        assert_eq!(paths.parents, vec![0, 2, 0, 1, UNREACHED, UNREACHED]);
+        assert_eq!((paths.reached(), paths.negative_cycle), (4, false));
+    }
+
+    let undirected = bellman_ford(&edges, nodes, 0, false);
+    let symmetric = WeightedAdjacencyList::symmetric_from_edges(&edges, nodes);
+    assert_eq!(
+        undirected.distances,
+        dijkstra(&symmetric, nodes, 0).distances
+    );
+    assert_eq!(undirected.distances[4], 1.5);
+
+    let mut negative = edges.clone();
+    negative.push((1, 2, -3.0));
+    assert!(bellman_ford(&negative, nodes, 0, true).negative_cycle);
+}
@@ -0,0 +1,194 @@
+//! Connected components by union-find, in a single pass over the edges.
+
+use std::time::{Duration, Instant};
+
+use crate::graph_iterator::EdgeMapper;
+
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    /// Each vertex's parent in the union-find forest; roots are their own parents.
+    pub roots: Vec<u32>,
+    /// The number of vertices that are not roots, which is the number of vertices less the
+    /// number of connected components.
+    pub non_roots: u32,
+    pub iterations: Vec<Duration>,
+}
+
+impl UnionFind {
+    /// The number of connected components.
+    pub fn components(&self) -> u32 {
+        self.roots.len() as u32 - self.non_roots
+    }
+
+    /// The root of `vertex`'s tree.
+    pub fn find(&self, mut vertex: u32) -> u32 {
+        while vertex != self.roots[vertex as usize] {
+            vertex = self.roots[vertex as usize];
+        }
+        vertex
+    }
+
+    /// Points every vertex directly at its root, so that `roots` maps vertices to components.
+    pub fn compress(&mut self) {
+        for vertex in 0..self.roots.len() {
+            let root = self.find(vertex as u32);
+            let mut node = vertex as u32;
+            while node != root {
+                let parent = self.roots[node as usize];
+                self.roots[node as usize] = root;
+                node = parent;
+            }
+        }
+    }
+
+    /// Labels each vertex with the smallest vertex identifier in its component, as
+    /// `label_propagation` does.
+    pub fn labels(&self) -> Vec<u32> {
+        let mut smallest = vec![u32::MAX; self.roots.len()];
+        let roots: Vec<u32> = (0..self.roots.len() as u32)
+            .map(|vertex| self.find(vertex))
+            .collect();
+        for (vertex, &root) in roots.iter().enumerate() {
+            let least = &mut smallest[root as usize];
+            *least = (*least).min(vertex as u32);
+        }
+        roots.iter().map(|&root| smallest[root as usize]).collect()
+    }
+
+    /// The size of each component, indexed by its root and zero for non-roots.
+    pub fn sizes(&self) -> Vec<u32> {
+        let mut sizes = vec![0u32; self.roots.len()];
+        for vertex in 0..self.roots.len() as u32 {
+            sizes[self.find(vertex) as usize] += 1;
+        }
+        sizes
+    }
+
+    /// Pairs of component size and the number of components of that size, by increasing size.
+    pub fn histogram(&self) -> Vec<(u32, u32)> {
+        let mut sizes: Vec<u32> = self.sizes().into_iter().filter(|&size| size > 0).collect();
+        sizes.sort_unstable();
+        let mut histogram: Vec<(u32, u32)> = Vec::new();
+        for size in sizes {
+            match histogram.last_mut() {
+                Some((last, count)) if *last == size => *count += 1,
+                _ => histogram.push((size, 1)),
+            }
+        }
+        histogram
+    }
+
+    /// The size of the largest component, zero if there are no vertices.
+    pub fn largest(&self) -> u32 {
+        self.sizes().into_iter().max().unwrap_or(0)
+    }
+}
+
+/// A union-find forest over vertices `0 .. nodes`, merging trees by rank.
+///
+/// Vertices are not checked against `nodes`, as this is the inner loop of `union_find`.
+pub(crate) struct DisjointSets {
+    roots: Vec<u32>, // u32 works, and is smaller than uint/u64
+    ranks: Vec<u8>,  // u8 should be large enough (n < 2^256)
+}
+
+impl DisjointSets {
+    pub(crate) fn new(nodes: u32) -> DisjointSets {
+        DisjointSets {
+            roots: (0..nodes).collect(),
+            ranks: vec![0u8; nodes as usize],
+        }
+    }
+
+    /// The root of `vertex`'s tree.
+    #[inline]
+    pub(crate) fn find(&self, mut vertex: u32) -> u32 {
+        // vertex = roots[vertex as usize];
+        // while vertex != roots[vertex as usize] { vertex = roots[vertex as usize]; }
+        vertex = unsafe { *self.roots.get_unchecked(vertex as usize) };
+        unsafe {
+            while vertex != *self.roots.get_unchecked(vertex as usize) {
+                vertex = *self.roots.get_unchecked(vertex as usize);
+            }
+        }
+        vertex
+    }
+
+    /// Merges the trees of `x` and `y`, returning whether they were different trees.
+    #[inline]
+    pub(crate) fn union(&mut self, x: u32, y: u32) -> bool {
+        let x = self.find(x);
+        let y = self.find(y);
+        if x != y {
+            unsafe {
+                match self.ranks[x as usize].cmp(&self.ranks[y as usize]) {
+                    std::cmp::Ordering::Less => *self.roots.get_unchecked_mut(x as usize) = y,
+                    std::cmp::Ordering::Greater => *self.roots.get_unchecked_mut(y as usize) = x,
+                    std::cmp::Ordering::Equal => {
+                        *self.roots.get_unchecked_mut(y as usize) = x;
+                        *self.ranks.get_unchecked_mut(x as usize) += 1
+                    }
+                }
+            }
+        }
+
+        // works for Hilbert curve order
+        // roots[x as usize] = min(x, y);
+        // roots[y as usize] = min(x, y);
+        x != y
+    }
+
+    pub(crate) fn into_roots(self) -> Vec<u32> {
+        self.roots
+    }
+}
+
+/// Unions the endpoints of each edge, by rank.
+pub fn union_find<G: EdgeMapper>(graph: &G, nodes: u32) -> UnionFind {
+    let mut sets = DisjointSets::new(nodes);
+
+    let timer = Instant::now();
+
+    graph.map_edges(|x, y| {
+        sets.union(x, y);
+    });
+
+    let roots = sets.into_roots();
+    let mut non_roots = 0u32;
+    for (i, &root) in roots.iter().enumerate() {
+        if i as u32 != root {
+            non_roots += 1;
+        }
+    }
+    UnionFind {
+        roots,
+        non_roots,
+        iterations: vec![timer.elapsed()],
+    }
+}
+
+#[test]
+fn test_union_find() {
+    let result = union_find(&vec![(3, 1), (1, 0), (4, 5)], 7);
+    assert_eq!((result.non_roots, result.components()), (3, 4));
+}
+
+#[test]
+fn test_union_find_labels() {
+    let graph = vec![(3, 1), (1, 0), (4, 5), (6, 5), (5, 3)];
+    let mut result = union_find(&graph, 9);
+    let labels = result.labels();
+    result.compress();
+    assert!(
+        (0..9).all(|vertex| result.roots[result.roots[vertex] as usize] == result.roots[vertex])
+    );
+    assert_eq!(result.labels(), labels);
+
+    let propagated = crate::algorithms::label_propagation(&graph, 9);
+    assert_eq!(
+        (labels, result.components()),
+        (propagated.labels, propagated.components)
+    );
+    assert_eq!(result.histogram(), vec![(1, 3), (6, 1)]);
+    assert_eq!(result.largest(), 6);
+}
@@ -0,0 +1,9 @@
+extern crate COST;
+extern crate clap;
+
+use clap::Parser;
+use COST::cli::{exit_on_error, Cost};
+
+fn main() {
+    exit_on_error(Cost::parse().command.run());
+}
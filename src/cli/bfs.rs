@@ -0,0 +1,137 @@
+//! Breadth-first search from a start vertex, printing the distance and parent of each vertex
+//! reached.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::adjacency::AdjacencyList;
+use crate::algorithms::bfs::{bfs, direction_optimizing, distance_to, Bfs, UNREACHED};
+use crate::error::{Error, Result};
+use crate::graph_iterator::{Mapper, NodesEdgesMemMapper};
+use crate::names::Names;
+use crate::with_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "BFS edge iterator application", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    #[arg(short, long)]
+    pub start_vertex: u32,
+
+    /// Follow edges only from source to destination, rather than either way
+    #[arg(long)]
+    pub directed: bool,
+
+    /// Switch between top-down and bottom-up levels, over adjacency lists built in memory or
+    /// the indexed vertex format
+    #[arg(long)]
+    pub direction_optimizing: bool,
+
+    /// Print only the distance to this vertex, stopping the search once it is reached
+    #[arg(long, value_name = "VERTEX", conflicts_with = "direction_optimizing")]
+    pub target: Option<u32>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let start_vertex = args.start_vertex;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+    if nodes <= start_vertex {
+        return Err(Error::invalid(
+            &graph_args.filename,
+            format!(
+                "nodes ({}) should be greater than start_vertex ({})",
+                nodes, start_vertex
+            ),
+        ));
+    }
+
+    if let Some(target) = args.target {
+        return reach(&args, nodes, target);
+    }
+
+    let start = Instant::now();
+    let result = match (args.direction_optimizing, &graph_args.mode) {
+        // the vertex formats are already adjacency lists, once indexed.
+        (true, Mapper::SymmetricVertex) => {
+            let mut graph = NodesEdgesMemMapper::try_new_symmetric(&graph_args.filename)?;
+            graph.index(false)?;
+            direction_optimizing(&graph, &graph, nodes, start_vertex)
+        }
+        (true, Mapper::Vertex) if args.directed => {
+            let mut graph = NodesEdgesMemMapper::try_new(&graph_args.filename)?;
+            graph.index(false)?;
+            let transposed = AdjacencyList::transposed_from_edges(&graph, nodes);
+            direction_optimizing(&graph, &transposed, nodes, start_vertex)
+        }
+        (true, _) => with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            if args.directed {
+                let forward = AdjacencyList::from_edges(graph, nodes);
+                let backward = forward.transpose();
+                direction_optimizing(&forward, &backward, nodes, start_vertex)
+            } else {
+                let symmetric = AdjacencyList::symmetric_from_edges(graph, nodes);
+                direction_optimizing(&symmetric, &symmetric, nodes, start_vertex)
+            }
+        })?,
+        (false, _) => with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            bfs(graph, nodes, start_vertex, args.directed)
+        })?,
+    };
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    print_output(&mut output, &result, &names)
+        .and_then(|()| output.flush())
+        .map_err(|err| Error::io(graph_args.output_name(), err))
+}
+
+/// Prints the distance from the start vertex to `target`, or that it is unreachable.
+fn reach(args: &Args, nodes: u32, target: u32) -> Result<()> {
+    let graph_args = &args.graph;
+    if nodes <= target {
+        return Err(Error::invalid(
+            &graph_args.filename,
+            format!(
+                "nodes ({}) should be greater than target ({})",
+                nodes, target
+            ),
+        ));
+    }
+
+    let start = Instant::now();
+    let distance = with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+        distance_to(graph, nodes, args.start_vertex, target, args.directed)
+    })?;
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        match distance {
+            Some(distance) => writeln!(output, "distance: {}", distance)?,
+            None => writeln!(output, "unreachable")?,
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
+
+/// Prints `vertex\tdistance\tparent` for each vertex reached.
+fn print_output<W: Write>(output: &mut W, result: &Bfs, names: &Names) -> std::io::Result<()> {
+    let reached = result.distances.iter().zip(result.parents.iter());
+    for (vertex, (&distance, &parent)) in reached.enumerate() {
+        if distance != UNREACHED {
+            let (vertex, parent) = (names.name(vertex as u32), names.name(parent));
+            writeln!(output, "{}\t{}\t{}", vertex, distance, parent)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Connected components by label propagation.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::algorithms::label_propagation;
+use crate::error::{Error, Result};
+use crate::names::Names;
+use crate::with_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Connected Components edge iterator application", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+
+    let start = Instant::now();
+    let result = with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+        label_propagation(graph, nodes)
+    })?;
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    print_output(&mut output, result.components, result.labels, &names)
+        .and_then(|()| output.flush())
+        .map_err(|err| Error::io(graph_args.output_name(), err))
+}
+
+/// Prints the number of components, then each vertex's label.
+pub(crate) fn print_output<W: Write>(
+    output: &mut W,
+    ccs: u32,
+    labels: Vec<u32>,
+    names: &Names,
+) -> std::io::Result<()> {
+    writeln!(output, "{} Connected Components", ccs)?;
+    for (i, label) in labels.into_iter().enumerate() {
+        writeln!(output, "{}\t{}", names.name(i as u32), names.name(label))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,712 @@
+//! Conversions between the on-disk graph formats.
+//!
+//! `vertex` ingests a text edge list as `.nodes`/`.edges` and their symmetric counterparts,
+//! `hilbert` rearranges those along a Hilbert curve as `.upper`/`.lower`, `offsets` indexes
+//! them for random access, and `compressed`,
+//! `merge` and `scan` work with delta-compressed Hilbert streams on stdin and stdout. `chunked`
+//! builds a delta-compressed Hilbert file from a text edge list in bounded memory, producing
+//! runs that `merge` also reads.
+
+use std::fs::{self, File};
+use std::io::{stdin, stdout, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use byteorder::{LittleEndian, NativeEndian, WriteBytesExt};
+use clap::{Parser, Subcommand};
+
+use crate::error::{Error, Result, STREAM};
+use crate::graph_iterator::{
+    EdgeMapper, Mapper, NodesEdgesMemMapper, ReaderMapper, WeightedEdgeMapper,
+    WeightedNodesEdgesMemMapper,
+};
+use crate::header::{Checksum, Header, HeaderWriter, Kind};
+use crate::hilbert_curve::{
+    convert_to_hilbert, convert_to_hilbert_external, convert_weighted_to_hilbert, encode, merge,
+    to_hilbert, to_hilbert_external, try_encode, write_hilbert_runs, BytewiseHilbert, Decoder,
+};
+use crate::metadata::write_sidecar;
+use crate::names::{invert, names_path, originals_path};
+
+use super::report_runtime;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Convert a text edge list to `.nodes`/`.edges` and `.binodes`/`.biedges`
+    Vertex(VertexArgs),
+    /// Convert `.nodes`/`.edges` to `.upper`/`.lower` in Hilbert order
+    Hilbert(HilbertArgs),
+    /// Index `.nodes`/`.edges` by vertex, writing `<prefix>.offsets`
+    Offsets(OffsetsArgs),
+    /// Convert a text edge list on stdin to a delta-compressed Hilbert stream on stdout
+    #[command(name = "parse_to_hilbert", alias = "compressed")]
+    Compressed(CompressedArgs),
+    /// Merge lz4-compressed Hilbert streams into one delta-compressed stream on stdout
+    Merge(MergeArgs),
+    /// Convert a text edge list to a delta-compressed Hilbert file, sorting it in lz4-compressed
+    /// runs under a memory budget and merging them
+    Chunked(ChunkedArgs),
+    /// Convert a delta-compressed Hilbert stream on stdin to per-vertex deltas and `offsets`
+    Scan,
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        let start = Instant::now();
+        match self {
+            Command::Vertex(args) => vertex(args),
+            Command::Hilbert(args) => hilbert(args),
+            Command::Offsets(args) => offsets(args),
+            Command::Compressed(args) => compressed(args),
+            Command::Merge(args) => merge_sources(args),
+            Command::Chunked(args) => chunked(args),
+            Command::Scan => scan(),
+        }?;
+        report_runtime(start);
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Converts a text edge list to vertex format",
+    after_help = "NOTE: <prefix>.nodes, <prefix>.edges, <prefix>.meta and, with --weighted, <prefix>.weights will be overwritten."
+)]
+pub struct VertexArgs {
+    pub source: String,
+
+    pub prefix: String,
+
+    /// Read a weight after each edge, one if missing, and write them to `<prefix>.weights`
+    #[arg(long)]
+    pub weighted: bool,
+
+    /// Prefix each file with a self-describing header
+    #[arg(long)]
+    pub header: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Converts a vertex-format graph to Hilbert format",
+    after_help = "NOTE: <prefix>.upper, <prefix>.lower, with --weighted, <prefix>.hweights, <prefix>.hmeta and, with --dense, <prefix>.names and <prefix>.originals will be overwritten."
+)]
+pub struct HilbertArgs {
+    pub prefix: String,
+
+    /// Rename vertices densely, in order of first appearance, recording the renaming in
+    /// `<prefix>.names` and its inverse in `<prefix>.originals`
+    #[arg(long)]
+    pub dense: bool,
+
+    /// Also rearrange `<prefix>.weights` along with the edges, as `<prefix>.hweights`
+    #[arg(long, conflicts_with = "memory")]
+    pub weighted: bool,
+
+    /// Prefix each file with a self-describing header
+    #[arg(long)]
+    pub header: bool,
+
+    #[command(flatten)]
+    pub budget: BudgetArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct OffsetsArgs {
+    pub prefix: String,
+
+    /// Index `.binodes`/`.biedges` instead, writing `<prefix>.bioffsets`
+    #[arg(long)]
+    pub symmetric: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CompressedArgs {
+    /// Prefix the stream with a self-describing header, holding the stream in memory until the
+    /// header can be written
+    #[arg(long)]
+    pub header: bool,
+
+    #[command(flatten)]
+    pub budget: BudgetArgs,
+}
+
+/// A bound on the memory used to sort edges into Hilbert order.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct BudgetArgs {
+    /// Sort at most this many MiB of edges at once, spilling sorted runs to disk and merging
+    /// them; repeated edges are kept or dropped just as without it
+    #[arg(long, value_name = "MIB", value_parser = clap::value_parser!(u64).range(1..))]
+    pub memory: Option<u64>,
+
+    /// Where to spill runs [default: the system's temporary directory]
+    #[arg(long, value_name = "DIR", requires = "memory")]
+    pub temp_dir: Option<PathBuf>,
+}
+
+impl BudgetArgs {
+    /// The number of edges to sort at once, if bounded.
+    pub fn edges(&self) -> Option<usize> {
+        self.memory.map(budget_edges)
+    }
+
+    pub fn temp_dir(&self) -> PathBuf {
+        self.temp_dir.clone().unwrap_or_else(std::env::temp_dir)
+    }
+}
+
+/// The number of edges, as Hilbert values, that fit in `mib` MiB.
+fn budget_edges(mib: u64) -> usize {
+    ((mib << 20) / mem::size_of::<u64>() as u64) as usize
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    #[arg(required = true)]
+    pub sources: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(after_help = "NOTE: <output> will be overwritten.")]
+pub struct ChunkedArgs {
+    pub source: String,
+
+    pub output: String,
+
+    /// Sort at most this many MiB of edges at once
+    #[arg(long, value_name = "MIB", default_value_t = 1024, value_parser = clap::value_parser!(u64).range(1..))]
+    pub memory: u64,
+
+    /// Where to write the runs [default: the system's temporary directory]
+    #[arg(long, value_name = "DIR")]
+    pub temp_dir: Option<PathBuf>,
+
+    /// Keep the runs, listing them on stderr, so that `merge` can combine them again
+    #[arg(long)]
+    pub keep_runs: bool,
+
+    /// Prefix the output with a self-describing header
+    #[arg(long)]
+    pub header: bool,
+}
+
+fn create(path: String, kind: Kind, header: bool) -> Result<HeaderWriter<BufWriter<File>>> {
+    File::create(&path)
+        .and_then(|file| HeaderWriter::new(BufWriter::new(file), kind, header))
+        .map_err(|err| Error::io(&path, err))
+}
+
+fn finish(writer: HeaderWriter<BufWriter<File>>, path: String) -> Result<()> {
+    writer
+        .finish()
+        .map(|_| ())
+        .map_err(|err| Error::io(&path, err))
+}
+
+pub fn vertex(args: VertexArgs) -> Result<()> {
+    let source = &args.source;
+    let target = &args.prefix;
+    let header = args.header;
+
+    File::open(source).map_err(|err| Error::io(source, err))?;
+    let reader_mapper =
+        ReaderMapper::new(|| BufReader::new(File::open(source).unwrap())).with_name(source);
+
+    let mut edge_writer = create(format!("{}.edges", target), Kind::Edges, header)?;
+    let mut node_writer = create(format!("{}.nodes", target), Kind::Nodes, header)?;
+    let mut weight_writer = match args.weighted {
+        true => Some(create(
+            format!("{}.weights", target),
+            Kind::Weights,
+            header,
+        )?),
+        false => None,
+    };
+
+    // `.nodes` must list each vertex once and in increasing order, so edges are written as they
+    // are read only while the input is grouped by source in increasing order; should a source go
+    // backwards, the remaining edges are still read for the symmetric copy, and the edge list is
+    // then read again, buffered and sorted.
+    let mut sorted = true;
+    let mut src = 0;
+    let mut cnt: u32 = 0;
+    let mut edges = 0u64;
+    let mut vec: Vec<Vec<u32>> = Vec::new();
+
+    let mut edge = |x: u32, y: u32, weight: f32| {
+        if sorted && x != src {
+            if x < src {
+                sorted = false;
+            } else {
+                if cnt > 0 {
+                    node_writer
+                        .write_u32::<NativeEndian>(src)
+                        .and_then(|()| node_writer.write_u32::<NativeEndian>(cnt))
+                        .expect("write error");
+                    cnt = 0;
+                }
+                src = x;
+            }
+        }
+        if sorted {
+            edge_writer
+                .write_u32::<NativeEndian>(y)
+                .expect("write error");
+            if let Some(weight_writer) = weight_writer.as_mut() {
+                weight_writer
+                    .write_f32::<NativeEndian>(weight)
+                    .expect("write error");
+            }
+            cnt += 1;
+        }
+        edges += 1;
+
+        let max = std::cmp::max(x, y) as usize;
+        if max >= vec.len() {
+            vec.resize(max + 1, vec![]);
+        }
+        if !vec[x as usize].contains(&y) {
+            vec[x as usize].push(y);
+        }
+        if !vec[y as usize].contains(&x) {
+            vec[y as usize].push(x);
+        }
+    };
+    if args.weighted {
+        reader_mapper.map_weighted_edges(&mut edge);
+    } else {
+        reader_mapper.map_edges(|x, y| edge(x, y, 1.0));
+    }
+
+    if sorted {
+        if cnt > 0 {
+            node_writer
+                .write_u32::<NativeEndian>(src)
+                .and_then(|()| node_writer.write_u32::<NativeEndian>(cnt))
+                .expect("write error");
+        }
+    } else {
+        // the old writers are dropped first, so their buffers are flushed before truncation.
+        drop((edge_writer, node_writer, weight_writer));
+        edge_writer = create(format!("{}.edges", target), Kind::Edges, header)?;
+        node_writer = create(format!("{}.nodes", target), Kind::Nodes, header)?;
+        weight_writer = match args.weighted {
+            true => Some(create(
+                format!("{}.weights", target),
+                Kind::Weights,
+                header,
+            )?),
+            false => None,
+        };
+
+        let mut directed: Vec<(u32, u32, f32)> = Vec::new();
+        if args.weighted {
+            reader_mapper.map_weighted_edges(|x, y, weight| directed.push((x, y, weight)));
+        } else {
+            reader_mapper.map_edges(|x, y| directed.push((x, y, 1.0)));
+        }
+        // stable, so each vertex's edges keep their order in the input.
+        directed.sort_by_key(|&(x, _, _)| x);
+        for group in directed.chunk_by(|a, b| a.0 == b.0) {
+            node_writer
+                .write_u32::<NativeEndian>(group[0].0)
+                .and_then(|()| node_writer.write_u32::<NativeEndian>(group.len() as u32))
+                .expect("write error");
+            for &(_, y, weight) in group.iter() {
+                edge_writer
+                    .write_u32::<NativeEndian>(y)
+                    .expect("write error");
+                if let Some(weight_writer) = weight_writer.as_mut() {
+                    weight_writer
+                        .write_f32::<NativeEndian>(weight)
+                        .expect("write error");
+                }
+            }
+        }
+    }
+
+    let vertices = Some(vec.len() as u64);
+    for writer in [&mut edge_writer, &mut node_writer] {
+        writer.describe(vertices, Some(edges), false);
+    }
+    finish(edge_writer, format!("{}.edges", target))?;
+    finish(node_writer, format!("{}.nodes", target))?;
+    if let Some(mut weight_writer) = weight_writer {
+        weight_writer.describe(vertices, Some(edges), false);
+        finish(weight_writer, format!("{}.weights", target))?;
+    }
+    let mut edge_bi_writer = create(format!("{}.biedges", target), Kind::SymmetricEdges, header)?;
+    let mut node_bi_writer = create(format!("{}.binodes", target), Kind::SymmetricNodes, header)?;
+
+    for (i, edge_vec) in vec.iter().enumerate() {
+        if edge_vec.is_empty() {
+            continue;
+        }
+        node_bi_writer
+            .write_u32::<NativeEndian>(i as u32)
+            .expect("write error");
+        node_bi_writer
+            .write_u32::<NativeEndian>(edge_vec.len() as u32)
+            .expect("write error");
+
+        for edge in edge_vec {
+            edge_bi_writer
+                .write_u32::<NativeEndian>(*edge)
+                .expect("write error");
+        }
+    }
+
+    let bi_edges = vec.iter().map(|edge_vec| edge_vec.len() as u64).sum();
+    for writer in [&mut edge_bi_writer, &mut node_bi_writer] {
+        writer.describe(vertices, Some(bi_edges), false);
+    }
+    finish(edge_bi_writer, format!("{}.biedges", target))?;
+    finish(node_bi_writer, format!("{}.binodes", target))?;
+    // written last, so that it is no older than the files it describes.
+    write_sidecar(&Mapper::Vertex, target, vec.len() as u64, edges)
+}
+
+pub fn hilbert(args: HilbertArgs) -> Result<()> {
+    let prefix = &args.prefix;
+    let dense = args.dense;
+    let header = args.header;
+
+    // open the inputs before truncating any outputs.
+    let (graph, weighted) = match args.weighted {
+        true => (None, Some(WeightedNodesEdgesMemMapper::try_new(prefix)?)),
+        false => (Some(NodesEdgesMemMapper::try_new(prefix)?), None),
+    };
+    let mut u_writer = create(format!("{}.upper", prefix), Kind::Upper, header)?;
+    let mut l_writer = create(format!("{}.lower", prefix), Kind::Lower, header)?;
+    let weights_path = format!("{}.hweights", prefix);
+    let mut w_writer = match weighted {
+        Some(_) => Some(create(weights_path.clone(), Kind::HilbertWeights, header)?),
+        None => None,
+    };
+
+    let mut max_vertex = None;
+    let mut edges = 0u64;
+    let mut tile = |ux: u16, uy: u16, c: u32, ls: &Vec<(u16, u16)>| {
+        u_writer.write_u16::<NativeEndian>(ux).unwrap();
+        u_writer.write_u16::<NativeEndian>(uy).unwrap();
+        u_writer.write_u32::<NativeEndian>(c).unwrap();
+        for &(lx, ly) in ls.iter() {
+            l_writer.write_u16::<NativeEndian>(lx).unwrap();
+            l_writer.write_u16::<NativeEndian>(ly).unwrap();
+            let x = ((ux as u32) << 16) | lx as u32;
+            let y = ((uy as u32) << 16) | ly as u32;
+            max_vertex = max_vertex.max(Some(x.max(y)));
+        }
+        edges += c as u64;
+    };
+    let names = if let (Some(graph), Some(w_writer)) = (&weighted, w_writer.as_mut()) {
+        convert_weighted_to_hilbert(graph, dense, |ux, uy, c, ls, ws| {
+            tile(ux, uy, c, ls);
+            for &weight in ws.iter() {
+                w_writer.write_f32::<NativeEndian>(weight).unwrap();
+            }
+        })
+    } else if let Some(graph) = &graph {
+        match args.budget.edges() {
+            Some(budget) => {
+                let dir = args.budget.temp_dir();
+                convert_to_hilbert_external(graph, dense, budget, &dir, tile)?
+            }
+            None => convert_to_hilbert(graph, dense, tile),
+        }
+    } else {
+        Vec::new()
+    };
+
+    let vertices = max_vertex.map_or(0, |max| max as u64 + 1);
+    for writer in [&mut u_writer, &mut l_writer]
+        .into_iter()
+        .chain(w_writer.as_mut())
+    {
+        writer.describe(Some(vertices), Some(edges), dense);
+    }
+    finish(u_writer, format!("{}.upper", prefix))?;
+    finish(l_writer, format!("{}.lower", prefix))?;
+    if let Some(w_writer) = w_writer {
+        finish(w_writer, weights_path)?;
+    }
+    if dense {
+        write_u32s(names_path(prefix), Kind::Names, &names, header)?;
+        write_u32s(
+            originals_path(prefix),
+            Kind::Originals,
+            &invert(&names),
+            header,
+        )?;
+    }
+    // written last, and apart from `.meta`, as dense renaming changes the vertex count.
+    write_sidecar(&Mapper::Hilbert, prefix, vertices, edges)
+}
+
+/// Writes `values` to `path` as `u32`s in host byte order, one per vertex.
+fn write_u32s(path: String, kind: Kind, values: &[u32], header: bool) -> Result<()> {
+    let mut writer = create(path.clone(), kind, header)?;
+    for &value in values.iter() {
+        writer
+            .write_u32::<NativeEndian>(value)
+            .map_err(|err| Error::io(&path, err))?;
+    }
+    writer.describe(Some(values.len() as u64), None, true);
+    finish(writer, path)
+}
+
+pub fn offsets(args: OffsetsArgs) -> Result<()> {
+    let mut graph = if args.symmetric {
+        NodesEdgesMemMapper::try_new_symmetric(&args.prefix)?
+    } else {
+        NodesEdgesMemMapper::try_new(&args.prefix)?
+    };
+    graph.index(true)
+}
+
+pub fn compressed(args: CompressedArgs) -> Result<()> {
+    let reader_mapper = ReaderMapper::new(|| BufReader::new(stdin()));
+    let mut writer = BufWriter::new(stdout());
+
+    if args.header {
+        // the header precedes the payload and stdout cannot seek, so buffer the payload.
+        let mut payload = Vec::new();
+        let (vertices, edges) = write_compressed(&reader_mapper, &args.budget, &mut payload)?;
+        let mut header = Header::new(Kind::Compressed);
+        header.vertices = Some(vertices);
+        header.edges = Some(edges);
+        header.checksum = Some(Checksum::of(&payload));
+        writer
+            .write_all(&header.to_bytes())
+            .and_then(|()| writer.write_all(&payload))
+            .map_err(|err| Error::io(STREAM, err))?;
+    } else {
+        write_compressed(&reader_mapper, &args.budget, &mut writer)?;
+    }
+    writer.flush().map_err(|err| Error::io(STREAM, err))
+}
+
+/// Writes the Hilbert values of `graph`'s edges to `writer` as deltas, sorting in runs if
+/// `budget` says, and returns the number of vertices and of edges written.
+///
+/// Deltas cannot encode a repeated value, so each distinct edge is written once, and the edge
+/// `(0, 0)`, whose value is zero, is left out, however the edges are sorted.
+fn write_compressed<G: EdgeMapper, W: Write>(
+    graph: &G,
+    budget: &BudgetArgs,
+    writer: &mut W,
+) -> Result<(u64, u64)> {
+    let hilbert = BytewiseHilbert::new();
+    let mut failed = None;
+    let mut max_vertex = None;
+    let mut edges = 0u64;
+
+    let mut prev = 0u64;
+    sorted_hilbert(graph, budget, |next| {
+        if next <= prev || failed.is_some() {
+            return;
+        }
+        failed = try_encode(writer, next - prev).err();
+        prev = next;
+        let (x, y) = hilbert.detangle(next);
+        max_vertex = max_vertex.max(Some(x.max(y)));
+        edges += 1;
+    })?;
+    match failed {
+        Some(err) => Err(err),
+        None => Ok((max_vertex.map_or(0, |max| max as u64 + 1), edges)),
+    }
+}
+
+/// Presents the Hilbert values of `graph`'s edges in order, sorting in runs if `budget` says.
+fn sorted_hilbert<G: EdgeMapper>(
+    graph: &G,
+    budget: &BudgetArgs,
+    output: impl FnMut(u64),
+) -> Result<()> {
+    match budget.edges() {
+        Some(edges) => to_hilbert_external(graph, edges, &budget.temp_dir(), output),
+        None => {
+            to_hilbert(graph, output);
+            Ok(())
+        }
+    }
+}
+
+pub fn merge_sources(args: MergeArgs) -> Result<()> {
+    let mut writer = BufWriter::new(stdout());
+    let mut prev = 0u64;
+    merge_runs(&args.sources, |next| {
+        encode(&mut writer, next - prev);
+        prev = next;
+    })?;
+    writer.flush().map_err(|err| Error::io(STREAM, err))
+}
+
+/// Merges the lz4-compressed Hilbert streams at `sources`, outputting each distinct non-zero
+/// value once, in order.
+fn merge_runs<P: AsRef<Path>>(sources: &[P], mut output: impl FnMut(u64)) -> Result<()> {
+    let mut vector = Vec::new();
+    for source in sources.iter() {
+        let source = source.as_ref().display().to_string();
+        let file = File::open(&source).map_err(|err| Error::io(&source, err))?;
+        let decoder =
+            lz4::Decoder::new(BufReader::new(file)).map_err(|err| Error::io(&source, err))?;
+        // each value of a run occurs there once.
+        vector.push(
+            Decoder::new(decoder)
+                .with_name(&source)
+                .map(|value| (value, 1)),
+        );
+    }
+
+    let mut prev = 0u64;
+    merge(vector, |next, _| {
+        assert!(prev <= next);
+        if prev < next {
+            output(next);
+            prev = next;
+        }
+    });
+    Ok(())
+}
+
+pub fn chunked(args: ChunkedArgs) -> Result<()> {
+    let source = &args.source;
+    File::open(source).map_err(|err| Error::io(source, err))?;
+    let reader_mapper =
+        ReaderMapper::new(|| BufReader::new(File::open(source).unwrap())).with_name(source);
+
+    let dir = args.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+    let runs = write_hilbert_runs(&reader_mapper, budget_edges(args.memory), &dir)?;
+    let merged = merge_into(&runs, &args.output, args.header);
+    if args.keep_runs {
+        for run in runs.iter() {
+            eprintln!("{}", run.display());
+        }
+    } else {
+        for run in runs.iter() {
+            let _ = fs::remove_file(run);
+        }
+    }
+    merged
+}
+
+/// Merges the runs at `sources` into a delta-compressed Hilbert file at `path`.
+fn merge_into(sources: &[PathBuf], path: &str, header: bool) -> Result<()> {
+    let hilbert = BytewiseHilbert::new();
+    let mut writer = create(path.to_owned(), Kind::Compressed, header)?;
+    let mut failed = None;
+    let mut max_vertex = None;
+    let mut edges = 0u64;
+
+    let mut prev = 0u64;
+    merge_runs(sources, |next| {
+        if failed.is_none() {
+            failed = try_encode(&mut writer, next - prev).err();
+        }
+        prev = next;
+        let (x, y) = hilbert.detangle(next);
+        max_vertex = max_vertex.max(Some(x.max(y)));
+        edges += 1;
+    })?;
+    if let Some(err) = failed {
+        return Err(match err {
+            Error::Io { source, .. } => Error::io(path, source),
+            err => err,
+        });
+    }
+
+    let vertices = Some(max_vertex.map_or(0, |max| max as u64 + 1));
+    writer.describe(vertices, Some(edges), false);
+    finish(writer, path.to_owned())
+}
+
+pub fn scan() -> Result<()> {
+    let mut bytes = 0u64;
+    let mut writer = BufWriter::new(stdout());
+    let offsets_file = File::create("offsets").map_err(|err| Error::io("offsets", err))?;
+    let mut offsets = BufWriter::new(offsets_file);
+    let mut buffer = Vec::new();
+
+    let mut prev_edge = 0u64;
+    let mut prev_node = 0u64;
+
+    let mut decoder = Decoder::new(BufReader::new(stdin()));
+    while let Some(next) = decoder.try_next()? {
+        let node = next >> 32;
+        let edge = next % (1 << 32);
+
+        while prev_node < node {
+            offsets
+                .write_u64::<LittleEndian>(bytes)
+                .map_err(|err| Error::io("offsets", err))?;
+            prev_node += 1;
+            prev_edge = 0u64;
+        }
+
+        let mut diff = edge - prev_edge;
+
+        while diff > 127 {
+            buffer.push(((diff & 127) as u8) + 128u8);
+            diff >>= 7;
+            bytes += 1;
+        }
+        buffer.push(diff as u8);
+        bytes += 1;
+
+        if buffer.len() > (1 << 20) {
+            writer
+                .write_all(&buffer[..])
+                .map_err(|err| Error::io(STREAM, err))?;
+            buffer.clear();
+        }
+
+        prev_edge = edge;
+    }
+
+    offsets.flush().map_err(|err| Error::io("offsets", err))?;
+    writer
+        .write_all(&buffer[..])
+        .and_then(|()| writer.flush())
+        .map_err(|err| Error::io(STREAM, err))
+}
+
+#[test]
+fn test_compressed_duplicates() {
+    use crate::graph_iterator::DeltaCompressedSliceMapper;
+
+    // enough edges to spill runs under a one MiB budget, each twice, and the edge (0, 0).
+    let mut graph = vec![(0, 0), (0, 0)];
+    for x in 0..100_000u32 {
+        let edge = (x * 7 % 3000, x * 13 % 5000);
+        graph.push(edge);
+        graph.push(edge);
+    }
+    let dir = std::env::temp_dir().join(format!("cost-compressed-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut expected: Vec<(u32, u32)> = graph.clone();
+    expected.sort();
+    expected.dedup();
+    expected.remove(0);
+    let in_memory = BudgetArgs::default();
+    let spilling = BudgetArgs {
+        memory: Some(1),
+        temp_dir: Some(dir.clone()),
+    };
+    for budget in [in_memory, spilling] {
+        let mut stream = Vec::new();
+        let counts = write_compressed(&graph, &budget, &mut stream).unwrap();
+        assert_eq!(counts, (5000, expected.len() as u64));
+        let mut edges = Vec::new();
+        DeltaCompressedSliceMapper::new(&stream).map_edges(|x, y| edges.push((x, y)));
+        edges.sort();
+        assert_eq!(edges, expected);
+    }
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
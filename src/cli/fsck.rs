@@ -0,0 +1,57 @@
+//! Checking graph files for truncation and inconsistencies.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::error::{Error, Result};
+use crate::validate::validate;
+
+use super::{report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Checks graph files for truncation and inconsistencies", long_about = None)]
+pub struct Args {
+    /// With `--nodes`, every vertex identifier must be less than the bound
+    #[command(flatten)]
+    pub graph: GraphArgs,
+}
+
+/// Prints any problems found, failing if there were some.
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+
+    let start = Instant::now();
+    let report = validate(&graph_args.mode, &graph_args.filename, graph_args.nodes);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        for problem in report.problems.iter() {
+            writeln!(output, "{}", problem)?;
+        }
+        if report.suppressed > 0 {
+            writeln!(output, "... and {} more problems", report.suppressed)?;
+        }
+        writeln!(output, "edges: {}", report.edges)?;
+        match report.max_vertex {
+            Some(max) => writeln!(output, "max vertex: {}", max)?,
+            None => writeln!(output, "max vertex: none")?,
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))?;
+
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err(Error::invalid(
+            &graph_args.filename,
+            format!(
+                "{} problems found",
+                report.problems.len() as u64 + report.suppressed
+            ),
+        ))
+    }
+}
@@ -0,0 +1,75 @@
+//! k-core decomposition, treating edges as undirected.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::adjacency::AdjacencyList;
+use crate::algorithms::{kcore, kcore_streaming};
+use crate::error::{Error, Result};
+use crate::graph_iterator::{Mapper, NodesEdgesMemMapper};
+use crate::with_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "k-core decomposition", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Peel by repeated passes over the edges, rather than over adjacency lists; needs the
+    /// `symmetric-vertex` mode
+    #[arg(long)]
+    pub streaming: bool,
+
+    /// Print `vertex\tcoreness` for each vertex
+    #[arg(long)]
+    pub per_vertex: bool,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+    if args.streaming && !matches!(graph_args.mode, Mapper::SymmetricVertex) {
+        return Err(Error::invalid(
+            &graph_args.filename,
+            "--streaming needs each edge once in each direction, as symmetric-vertex mode has them",
+        ));
+    }
+
+    let start = Instant::now();
+    let result = match graph_args.mode {
+        // the symmetric vertex format is already symmetric adjacency lists, once indexed.
+        Mapper::SymmetricVertex if !args.streaming => {
+            let mut graph = NodesEdgesMemMapper::try_new_symmetric(&graph_args.filename)?;
+            graph.index(false)?;
+            kcore(&graph, nodes)
+        }
+        _ => with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            if args.streaming {
+                kcore_streaming(graph, nodes)
+            } else {
+                let mut symmetric = AdjacencyList::symmetric_from_edges(graph, nodes);
+                symmetric.sort_dedup();
+                kcore(&symmetric, nodes)
+            }
+        })?,
+    };
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        writeln!(output, "degeneracy: {}", result.degeneracy)?;
+        if args.per_vertex {
+            for (vertex, coreness) in result.coreness.iter().enumerate() {
+                writeln!(output, "{}\t{}", names.name(vertex as u32), coreness)?;
+            }
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
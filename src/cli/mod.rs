@@ -0,0 +1,161 @@
+//! Subcommands of the `cost` binary, which the single-purpose binaries also wrap.
+//!
+//! Algorithms share `GraphArgs`: the mode and filename of the graph, an optional bound on
+//! vertex identifiers, where to write results, and whether to report per-round timings.
+//! Results go to the output, and timings to `stderr`.
+
+use std::fs::File;
+use std::io::{stdout, BufWriter, Write};
+
+use clap::{Parser, Subcommand};
+
+use crate::error::{Error, Result, STREAM};
+use crate::graph_iterator::Mapper;
+use crate::metadata::resolve_nodes;
+use crate::names::Names;
+
+pub mod bfs;
+pub mod cc;
+pub mod convert;
+pub mod fsck;
+pub mod kcore;
+pub mod msf;
+pub mod pagerank;
+pub mod scc;
+pub mod sssp;
+pub mod stats;
+pub mod triangles;
+pub mod uf;
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct GraphArgs {
+    /// Print timings for each round to stderr
+    #[arg(short, long, action)]
+    pub print_rounds: bool,
+
+    #[arg(short, long)]
+    pub mode: Mapper,
+
+    #[arg(short, long)]
+    pub filename: String,
+
+    /// Bound on vertex identifiers, read from the graph's metadata if omitted
+    #[arg(short, long)]
+    pub nodes: Option<u32>,
+
+    /// Write results to this file rather than stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Print vertices of a densely renamed graph by their original identifiers, read from
+    /// `<filename>.originals`
+    #[arg(long)]
+    pub original_ids: bool,
+}
+
+impl GraphArgs {
+    /// The number of vertices to allocate state for, checking any explicit bound.
+    pub fn nodes(&self) -> Result<u32> {
+        resolve_nodes(&self.mode, &self.filename, self.nodes).map(|(nodes, _)| nodes)
+    }
+
+    /// How to print the vertices of results, which is as they are unless `--original-ids`.
+    pub fn names(&self, nodes: u32) -> Result<Names> {
+        match self.original_ids {
+            true => Names::try_new(&self.filename, nodes),
+            false => Ok(Names::identity()),
+        }
+    }
+
+    /// The name of the output, for error messages.
+    pub fn output_name(&self) -> &str {
+        self.output.as_deref().unwrap_or(STREAM)
+    }
+
+    /// Opens the output for writing results.
+    pub fn output(&self) -> Result<Box<dyn Write>> {
+        match &self.output {
+            Some(path) => File::create(path)
+                .map(|file| Box::new(BufWriter::new(file)) as Box<dyn Write>)
+                .map_err(|err| Error::io(path, err)),
+            None => Ok(Box::new(BufWriter::new(stdout()))),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Single-threaded graph processing", long_about = None)]
+pub struct Cost {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Convert between graph formats
+    #[command(subcommand)]
+    Convert(convert::Command),
+    /// Count vertices and edges, and record them in a sidecar
+    Stats(stats::Args),
+    /// Check graph files for truncation and inconsistencies
+    Fsck(fsck::Args),
+    /// PageRank
+    Pagerank(pagerank::Args),
+    /// Connected components by label propagation
+    Cc(cc::Args),
+    /// Connected components by union-find
+    Uf(uf::Args),
+    /// Breadth-first search from a start vertex
+    Bfs(bfs::Args),
+    /// Strongly connected components
+    Scc(scc::Args),
+    /// Single-source shortest paths over weighted edges
+    Sssp(sssp::Args),
+    /// Minimum spanning forest over weighted edges
+    Msf(msf::Args),
+    /// Triangles and local clustering coefficients
+    Triangles(triangles::Args),
+    /// Coreness of each vertex and the degeneracy
+    Kcore(kcore::Args),
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Command::Convert(command) => command.run(),
+            Command::Stats(args) => stats::run(args),
+            Command::Fsck(args) => fsck::run(args),
+            Command::Pagerank(args) => pagerank::run(args),
+            Command::Cc(args) => cc::run(args),
+            Command::Uf(args) => uf::run(args),
+            Command::Bfs(args) => bfs::run(args),
+            Command::Scc(args) => scc::run(args),
+            Command::Sssp(args) => sssp::run(args),
+            Command::Msf(args) => msf::run(args),
+            Command::Triangles(args) => triangles::run(args),
+            Command::Kcore(args) => kcore::run(args),
+        }
+    }
+}
+
+/// Reports `result`'s error, if any, and exits with a failure status.
+pub fn exit_on_error(result: Result<()>) {
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Reports the time elapsed by the end of each iteration, if asked to.
+pub fn print_rounds(args: &GraphArgs, iterations: &[std::time::Duration]) {
+    if args.print_rounds {
+        for (iteration, elapsed) in iterations.iter().enumerate() {
+            eprintln!("{:?}\titeration: {}", elapsed, iteration);
+        }
+    }
+}
+
+/// Reports the end-to-end runtime of a command that began at `start`.
+pub fn report_runtime(start: std::time::Instant) {
+    eprintln!("E2E runtime: {} ns", start.elapsed().as_nanos());
+}
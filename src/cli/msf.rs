@@ -0,0 +1,57 @@
+//! Minimum spanning forests of weighted graphs, printing the forest's weight and edges.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::{Parser, ValueEnum};
+
+use crate::algorithms::spanning_forest::{boruvka, kruskal};
+use crate::error::{Error, Result};
+use crate::with_weighted_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Algorithm {
+    /// Sort the edges in memory and add those that join two trees
+    Kruskal,
+    /// Add the lightest edge leaving each tree, in passes over the edges
+    Boruvka,
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Minimum spanning forest over weighted edges", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    #[arg(long, value_enum, default_value_t = Algorithm::Kruskal)]
+    pub algorithm: Algorithm,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+
+    let start = Instant::now();
+    let result = with_weighted_graph!(graph_args.mode, &graph_args.filename, |graph| {
+        match args.algorithm {
+            Algorithm::Kruskal => kruskal(graph, nodes),
+            Algorithm::Boruvka => boruvka(graph, nodes),
+        }
+    })?;
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        writeln!(output, "forest weight: {}", result.weight)?;
+        writeln!(output, "{} edges", result.edges.len())?;
+        for &(x, y, weight) in result.edges.iter() {
+            writeln!(output, "{}\t{}\t{}", names.name(x), names.name(y), weight)?;
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
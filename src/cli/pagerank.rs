@@ -0,0 +1,260 @@
+//! PageRank, with configurable iterations, damping and convergence tolerance.
+//!
+//! With `--seeds` teleportation goes to a seed set, which makes it personalized PageRank (or
+//! random walk with restart), and with `--source` the personalized PageRank of one vertex is
+//! approximated by pushing rank from it.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::Instant;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use clap::{Parser, ValueEnum};
+
+use crate::algorithms::pagerank::{pagerank, personalization, push, top, Config, Dangling};
+use crate::algorithms::parallel_pagerank;
+use crate::error::{Error, Result};
+use crate::names::Names;
+use crate::{with_graph, with_parallel_graph};
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+/// How `--ranks` writes per-vertex ranks.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum RankFormat {
+    /// One `vertex\trank` line per vertex
+    Text,
+    /// Little-endian `f32`s, indexed by vertex
+    F32,
+    /// Little-endian `f64`s, indexed by vertex
+    F64,
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about = "PageRank edge iterator application", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// The most iterations to perform
+    #[arg(long, default_value_t = 20)]
+    pub iterations: usize,
+
+    /// The probability of following an edge rather than teleporting
+    #[arg(long, default_value_t = 0.85)]
+    pub damping: f32,
+
+    /// Stop once successive rank vectors, normalised to sum to one, are within this L1 distance
+    #[arg(long)]
+    pub tolerance: Option<f64>,
+
+    /// Where the rank of vertices without out-edges goes [default: personalization with
+    /// `--seeds`, otherwise uniform]
+    #[arg(long, value_enum)]
+    pub dangling: Option<Dangling>,
+
+    /// Teleport to the vertices in this file, one per line, each optionally followed by a weight
+    #[arg(long, value_name = "FILE", conflicts_with = "source")]
+    pub seeds: Option<String>,
+
+    /// Approximate the personalized PageRank of this vertex by pushing rank from it
+    #[arg(long, value_name = "VERTEX")]
+    pub source: Option<u32>,
+
+    /// With `--source`, push from vertices whose residual is at least this times their degree
+    #[arg(long, default_value_t = 1e-7)]
+    pub epsilon: f64,
+
+    /// Write per-vertex ranks to this file
+    #[arg(long)]
+    pub ranks: Option<String>,
+
+    /// The format of `--ranks`
+    #[arg(long, value_enum, default_value_t = RankFormat::Text)]
+    pub format: RankFormat,
+
+    /// Also print the highest ranked vertices
+    #[arg(long, value_name = "K")]
+    pub top: Option<usize>,
+
+    /// Run on this many threads, each accumulating ranks for a share of the edges (vertex,
+    /// symmetric-vertex and hilbert modes, uniform dangling rank only)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["seeds", "source", "tolerance"]
+    )]
+    pub threads: Option<u32>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+    if !(0.0..=1.0).contains(&args.damping) {
+        return Err(Error::invalid(
+            &graph_args.filename,
+            format!("damping ({}) must be between 0 and 1", args.damping),
+        ));
+    }
+
+    let start = Instant::now();
+    // the ranks, their timings, and how far they may be from converged.
+    let (ranks, iterations, remaining) = match (args.source, args.threads) {
+        (None, Some(threads)) => {
+            if args
+                .dangling
+                .is_some_and(|dangling| dangling != Dangling::Uniform)
+            {
+                return Err(Error::invalid(
+                    &graph_args.filename,
+                    "--threads spreads dangling rank uniformly only",
+                ));
+            }
+            let result = with_parallel_graph!(graph_args.mode, &graph_args.filename, |graph| {
+                parallel_pagerank(
+                    graph,
+                    nodes,
+                    args.iterations,
+                    args.damping,
+                    threads as usize,
+                )
+            })?;
+            (result.ranks, result.iterations, None)
+        }
+        (Some(source), _) => {
+            if source >= nodes || args.epsilon <= 0.0 {
+                return Err(Error::invalid(
+                    &graph_args.filename,
+                    format!(
+                        "source ({}) must be less than nodes ({}), and epsilon ({}) positive",
+                        source, nodes, args.epsilon
+                    ),
+                ));
+            }
+            let result = with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+                push(graph, nodes, source, args.damping, args.epsilon)
+            })?;
+            let residual = ("residual", result.residual);
+            (result.ranks, result.iterations, Some(residual))
+        }
+        (None, None) => {
+            let seeds = match &args.seeds {
+                Some(path) => Some(read_seeds(path, nodes)?),
+                None => None,
+            };
+            let default_dangling = match seeds {
+                Some(_) => Dangling::Personalization,
+                None => Dangling::Uniform,
+            };
+            let config = Config {
+                iterations: args.iterations,
+                alpha: args.damping,
+                tolerance: args.tolerance,
+                dangling: args.dangling.unwrap_or(default_dangling),
+                personalization: seeds.map(|seeds| personalization(&seeds, nodes)),
+            };
+            let result = with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+                pagerank(graph, nodes, &config)
+            })?;
+            let delta = result.delta.map(|delta| ("delta", delta));
+            (result.ranks, result.iterations, delta)
+        }
+    };
+    print_rounds(graph_args, &iterations);
+    report_runtime(start);
+
+    if let Some(path) = &args.ranks {
+        write_ranks(path, &ranks, args.format, &names)?;
+    }
+
+    let max_val = ranks.iter().fold(0f32, |max, &rank| max.max(rank));
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        writeln!(output, "iterations: {}", iterations.len())?;
+        if let Some((what, remaining)) = remaining {
+            writeln!(output, "{}: {}", what, remaining)?;
+        }
+        writeln!(output, "maxVal: {}", max_val)?;
+        if let Some(k) = args.top {
+            for (vertex, rank) in top(&ranks, k) {
+                writeln!(output, "{}\t{}", names.name(vertex), rank)?;
+            }
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
+
+/// Reads seed vertices and their weights from `path`, one per line.
+///
+/// A line holds a vertex identifier, optionally followed by a positive weight (one if
+/// omitted). Blank lines and lines starting with `#` are ignored.
+pub fn read_seeds(path: &str, nodes: u32) -> Result<Vec<(u32, f32)>> {
+    let file = File::open(path).map_err(|err| Error::io(path, err))?;
+    let mut seeds = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| Error::io(path, err))?;
+        let number = index + 1;
+        let mut fields = line.split_whitespace();
+        let vertex = match fields.next() {
+            Some(field) if !field.starts_with('#') => field,
+            _ => continue,
+        };
+        let vertex: u32 = vertex.parse().map_err(|err| {
+            Error::parse(
+                path,
+                number,
+                format!("malformed seed {:?}: {}", vertex, err),
+            )
+        })?;
+        if vertex >= nodes {
+            return Err(Error::parse(
+                path,
+                number,
+                format!("seed {} is not less than nodes ({})", vertex, nodes),
+            ));
+        }
+        let weight = match fields.next() {
+            Some(weight) => weight.parse::<f32>().map_err(|err| {
+                Error::parse(
+                    path,
+                    number,
+                    format!("malformed weight {:?}: {}", weight, err),
+                )
+            })?,
+            None => 1f32,
+        };
+        if !(weight > 0.0 && weight.is_finite()) {
+            return Err(Error::parse(
+                path,
+                number,
+                format!("weight {} is not positive", weight),
+            ));
+        }
+        seeds.push((vertex, weight));
+    }
+    if seeds.is_empty() {
+        return Err(Error::invalid(path, "no seeds"));
+    }
+    Ok(seeds)
+}
+
+/// Writes `ranks` to `path` in `format`, naming vertices by `names` in text.
+///
+/// The binary formats are indexed by vertex as the graph numbers them, whatever `names`.
+pub fn write_ranks(path: &str, ranks: &[f32], format: RankFormat, names: &Names) -> Result<()> {
+    let write = || -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (vertex, &rank) in ranks.iter().enumerate() {
+            match format {
+                RankFormat::Text => writeln!(writer, "{}\t{}", names.name(vertex as u32), rank)?,
+                RankFormat::F32 => writer.write_f32::<LittleEndian>(rank)?,
+                RankFormat::F64 => writer.write_f64::<LittleEndian>(rank as f64)?,
+            }
+        }
+        writer.flush()
+    };
+    write().map_err(|err| Error::io(path, err))
+}
@@ -0,0 +1,63 @@
+//! Strongly connected components, following edges from source to destination.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::adjacency::AdjacencyList;
+use crate::algorithms::strongly_connected;
+use crate::error::{Error, Result};
+use crate::graph_iterator::{Mapper, NodesEdgesMemMapper};
+use crate::with_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Strongly connected components of a directed graph", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Print each vertex's component, labelled by its smallest vertex
+    #[arg(long)]
+    pub labels: bool,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+
+    let start = Instant::now();
+    let result = match graph_args.mode {
+        // the vertex format is already adjacency lists, once indexed.
+        Mapper::Vertex => {
+            let mut graph = NodesEdgesMemMapper::try_new(&graph_args.filename)?;
+            graph.index(false)?;
+            strongly_connected(&graph, nodes)
+        }
+        _ => with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            strongly_connected(&AdjacencyList::from_edges(graph, nodes), nodes)
+        })?,
+    };
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        writeln!(
+            output,
+            "{} Strongly Connected Components",
+            result.components
+        )?;
+        writeln!(output, "largest component: {}", result.largest)?;
+        if args.labels {
+            for (i, label) in result.labels.iter().enumerate() {
+                writeln!(output, "{}\t{}", names.name(i as u32), names.name(*label))?;
+            }
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
@@ -0,0 +1,167 @@
+//! Single-source shortest paths over weighted edges, printing the distance and parent of each
+//! vertex reached.
+//!
+//! Weights come from the third column of a text edge list in `reader` mode, from
+//! `<prefix>.weights` in `vertex` mode, and from `<prefix>.hweights` in `hilbert` mode.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::{Parser, ValueEnum};
+
+use crate::adjacency::WeightedAdjacency;
+use crate::adjacency::WeightedAdjacencyList;
+use crate::algorithms::bfs::UNREACHED;
+use crate::algorithms::sssp::{bellman_ford, delta_stepping, dijkstra, ShortestPaths};
+use crate::error::{Error, Result};
+use crate::graph_iterator::{Mapper, WeightedEdgeMapper, WeightedNodesEdgesMemMapper};
+use crate::names::Names;
+use crate::with_weighted_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Algorithm {
+    /// Settle vertices in order of distance, over adjacency lists; weights must not be negative
+    Dijkstra,
+    /// Settle vertices in buckets of distances `--delta` wide, over adjacency lists; weights
+    /// must not be negative
+    DeltaStepping,
+    /// Relax every edge in passes over the edges until nothing improves
+    BellmanFord,
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Single-source shortest paths over weighted edges", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    #[arg(short, long)]
+    pub start_vertex: u32,
+
+    /// Follow edges only from source to destination, rather than either way
+    #[arg(long)]
+    pub directed: bool,
+
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    pub algorithm: Algorithm,
+
+    /// The width of the distance buckets of delta-stepping
+    #[arg(long, default_value_t = 1.0)]
+    pub delta: f32,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let name = &graph_args.filename[..];
+    let start_vertex = args.start_vertex;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+    if nodes <= start_vertex {
+        return Err(Error::invalid(
+            name,
+            format!(
+                "nodes ({}) should be greater than start_vertex ({})",
+                nodes, start_vertex
+            ),
+        ));
+    }
+    if !(args.delta > 0.0 && args.delta.is_finite()) {
+        return Err(Error::invalid(
+            name,
+            format!("delta ({}) must be positive and finite", args.delta),
+        ));
+    }
+
+    let start = Instant::now();
+    let result = match (&graph_args.mode, args.algorithm) {
+        // the vertex format is already weighted adjacency lists, once indexed.
+        (Mapper::Vertex, Algorithm::Dijkstra | Algorithm::DeltaStepping) if args.directed => {
+            let mut graph = WeightedNodesEdgesMemMapper::try_new(name)?;
+            check_weights(&graph, name)?;
+            graph.index(false)?;
+            settle(&graph, nodes, &args)
+        }
+        _ => with_weighted_graph!(graph_args.mode, name, |graph| {
+            shortest_paths(graph, name, nodes, &args)
+        })??,
+    };
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    if result.negative_cycle {
+        return Err(Error::invalid(
+            name,
+            format!("a negative cycle is reachable from {}", start_vertex),
+        ));
+    }
+
+    let mut output = graph_args.output()?;
+    print_output(&mut output, &result, &names)
+        .and_then(|()| output.flush())
+        .map_err(|err| Error::io(graph_args.output_name(), err))
+}
+
+fn shortest_paths<G: WeightedEdgeMapper>(
+    graph: &G,
+    name: &str,
+    nodes: u32,
+    args: &Args,
+) -> Result<ShortestPaths> {
+    Ok(match args.algorithm {
+        Algorithm::BellmanFord => bellman_ford(graph, nodes, args.start_vertex, args.directed),
+        Algorithm::Dijkstra | Algorithm::DeltaStepping => {
+            check_weights(graph, name)?;
+            let lists = match args.directed {
+                true => WeightedAdjacencyList::from_edges(graph, nodes),
+                false => WeightedAdjacencyList::symmetric_from_edges(graph, nodes),
+            };
+            settle(&lists, nodes, args)
+        }
+    })
+}
+
+/// Runs whichever of the algorithms over adjacency lists `args` asks for.
+fn settle<A: WeightedAdjacency>(graph: &A, nodes: u32, args: &Args) -> ShortestPaths {
+    match args.algorithm {
+        Algorithm::DeltaStepping => delta_stepping(graph, nodes, args.start_vertex, args.delta),
+        _ => dijkstra(graph, nodes, args.start_vertex),
+    }
+}
+
+/// Fails if any weight is negative, or not a number, which Dijkstra's algorithm cannot handle.
+fn check_weights<G: WeightedEdgeMapper>(graph: &G, name: &str) -> Result<()> {
+    let mut invalid = None;
+    graph.map_weighted_edges(|x, y, weight| {
+        if invalid.is_none() && (weight < 0.0 || weight.is_nan()) {
+            invalid = Some((x, y, weight));
+        }
+    });
+    match invalid {
+        Some((x, y, weight)) => Err(Error::invalid(
+            name,
+            format!(
+                "edge ({}, {}) has weight {}; use --algorithm bellman-ford",
+                x, y, weight
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Prints `vertex\tdistance\tparent` for each vertex reached.
+fn print_output<W: Write>(
+    output: &mut W,
+    result: &ShortestPaths,
+    names: &Names,
+) -> std::io::Result<()> {
+    let reached = result.distances.iter().zip(result.parents.iter());
+    for (vertex, (&distance, &parent)) in reached.enumerate() {
+        if parent != UNREACHED {
+            let (vertex, parent) = (names.name(vertex as u32), names.name(parent));
+            writeln!(output, "{}\t{}\t{}", vertex, distance, parent)?;
+        }
+    }
+    Ok(())
+}
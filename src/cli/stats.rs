@@ -0,0 +1,91 @@
+//! Counting vertices and edges, recorded in a `<prefix>.meta` sidecar (`<prefix>.hmeta` for the
+//! Hilbert files).
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::error::{Error, Result};
+use crate::graph_iterator::EdgeMapper;
+use crate::metadata::{sidecar_path, write_sidecar};
+use crate::with_graph;
+
+use super::{report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Counts vertices and edges of a graph", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let name = &graph_args.filename;
+
+    let start = Instant::now();
+    let stats = with_graph!(graph_args.mode, name, |graph| stats(graph))?;
+    if let Some(nodes) = graph_args.nodes {
+        if (nodes as u64) < stats.vertices {
+            return Err(Error::invalid(
+                name,
+                format!(
+                    "nodes ({}) must exceed the largest vertex identifier ({})",
+                    nodes,
+                    stats.vertices - 1
+                ),
+            ));
+        }
+    }
+
+    // record the counts so algorithms need not be told (or scan for) the vertex count, keeping
+    // any vertices beyond the largest identifier that `--nodes` accounts for.
+    let vertices = graph_args
+        .nodes
+        .map_or(stats.vertices, |nodes| stats.vertices.max(nodes as u64));
+    write_sidecar(&graph_args.mode, name, vertices, stats.edges)?;
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    writeln!(output, "max x: {}", stats.max_x)
+        .and_then(|()| writeln!(output, "max y: {}", stats.max_y))
+        .and_then(|()| writeln!(output, "edges: {}", stats.edges))
+        .and_then(|()| writeln!(output, "wrote {}", sidecar_path(&graph_args.mode, name)))
+        .and_then(|()| output.flush())
+        .map_err(|err| Error::io(graph_args.output_name(), err))
+}
+
+struct Stats {
+    max_x: u32,
+    max_y: u32,
+    edges: u64,
+    vertices: u64,
+}
+
+fn stats<G: EdgeMapper>(graph: &G) -> Stats {
+    let mut max_x = 0;
+    let mut max_y = 0;
+    let mut edges = 0u64;
+    graph.map_edges(|x, y| {
+        if max_x < x {
+            max_x = x;
+        }
+        if max_y < y {
+            max_y = y;
+        }
+        edges += 1;
+    });
+
+    let vertices = if edges > 0 {
+        std::cmp::max(max_x, max_y) as u64 + 1
+    } else {
+        0
+    };
+    Stats {
+        max_x,
+        max_y,
+        edges,
+        vertices,
+    }
+}
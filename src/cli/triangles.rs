@@ -0,0 +1,65 @@
+//! Triangle counting and local clustering coefficients, treating edges as undirected.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::adjacency::AdjacencyList;
+use crate::algorithms::triangles;
+use crate::error::{Error, Result};
+use crate::graph_iterator::{Mapper, NodesEdgesMemMapper};
+use crate::with_graph;
+
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Triangle counting and clustering coefficients", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Print `vertex\ttriangles\tclustering` for each vertex
+    #[arg(long)]
+    pub per_vertex: bool,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+
+    let start = Instant::now();
+    let result = match graph_args.mode {
+        // the symmetric vertex format is already symmetric adjacency lists, once indexed.
+        Mapper::SymmetricVertex => {
+            let mut graph = NodesEdgesMemMapper::try_new_symmetric(&graph_args.filename)?;
+            graph.index(false)?;
+            triangles(&graph, nodes)
+        }
+        _ => with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            triangles(&AdjacencyList::symmetric_from_edges(graph, nodes), nodes)
+        })?,
+    };
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        writeln!(output, "{} triangles", result.total)?;
+        writeln!(
+            output,
+            "average clustering: {}",
+            result.average_clustering()
+        )?;
+        if args.per_vertex {
+            let counts = result.per_vertex.iter().zip(result.clustering.iter());
+            for (vertex, (count, clustering)) in counts.enumerate() {
+                let vertex = names.name(vertex as u32);
+                writeln!(output, "{}\t{}\t{}", vertex, count, clustering)?;
+            }
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
@@ -0,0 +1,82 @@
+//! Connected components by union-find.
+//!
+//! `--labels` prints components as `cc` does, labelled by their smallest vertex, so that the
+//! outputs of the two can be diffed.
+
+use std::io::Write;
+use std::time::Instant;
+
+use clap::Parser;
+
+use crate::algorithms::{parallel_union_find, union_find};
+use crate::error::{Error, Result};
+use crate::{with_graph, with_parallel_graph};
+
+use super::cc::print_output;
+use super::{print_rounds, report_runtime, GraphArgs};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Union-find edge iterator application", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    pub graph: GraphArgs,
+
+    /// Print each vertex's component, in place of the number of non-roots
+    #[arg(long)]
+    pub labels: bool,
+
+    /// Print the number of components of each size
+    #[arg(long)]
+    pub histogram: bool,
+
+    /// Print the size of the largest component
+    #[arg(long)]
+    pub largest: bool,
+
+    /// Union edges on this many threads, sharing one atomic forest (vertex, symmetric-vertex
+    /// and hilbert modes)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let graph_args = &args.graph;
+    let nodes = graph_args.nodes()?;
+    let names = graph_args.names(nodes)?;
+
+    let start = Instant::now();
+    let mut result = match args.threads {
+        Some(threads) => with_parallel_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            parallel_union_find(graph, nodes, threads as usize)
+        })?,
+        None => with_graph!(graph_args.mode, &graph_args.filename, |graph| {
+            union_find(graph, nodes)
+        })?,
+    };
+    print_rounds(graph_args, &result.iterations);
+    report_runtime(start);
+
+    if args.labels || args.histogram || args.largest {
+        result.compress();
+    }
+
+    let mut output = graph_args.output()?;
+    let mut print = || -> std::io::Result<()> {
+        if args.labels {
+            print_output(&mut output, result.components(), result.labels(), &names)?;
+        } else {
+            writeln!(output, "{} non-roots found", result.non_roots)?;
+        }
+        if args.histogram {
+            writeln!(output, "size\tcomponents")?;
+            for (size, count) in result.histogram() {
+                writeln!(output, "{}\t{}", size, count)?;
+            }
+        }
+        if args.largest {
+            writeln!(output, "largest component: {}", result.largest())?;
+        }
+        output.flush()
+    };
+    print().map_err(|err| Error::io(graph_args.output_name(), err))
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::io;
+
+/// Name reported for readers and writers that are not backed by a named file.
+pub const STREAM: &str = "<stream>";
+
+/// Errors produced while opening, mapping, parsing or encoding graph data.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O operation on `path` failed.
+    Io { path: String, source: io::Error },
+    /// Line `line` (1-based) of the textual edge list `path` could not be parsed.
+    Parse {
+        path: String,
+        line: usize,
+        cause: String,
+    },
+    /// The binary data in `path` is malformed at byte `offset`.
+    Corrupt {
+        path: String,
+        offset: u64,
+        cause: String,
+    },
+    /// The contents of `path` are well-formed but unusable.
+    Invalid { path: String, cause: String },
+    /// `path` was written on a host of the opposite byte order, so its records cannot be mapped.
+    ForeignEndian { path: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn io(path: &str, source: io::Error) -> Error {
+        Error::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub fn parse(path: &str, line: usize, cause: impl ToString) -> Error {
+        Error::Parse {
+            path: path.to_owned(),
+            line,
+            cause: cause.to_string(),
+        }
+    }
+
+    pub fn corrupt(path: &str, offset: u64, cause: impl ToString) -> Error {
+        Error::Corrupt {
+            path: path.to_owned(),
+            offset,
+            cause: cause.to_string(),
+        }
+    }
+
+    pub fn invalid(path: &str, cause: impl ToString) -> Error {
+        Error::Invalid {
+            path: path.to_owned(),
+            cause: cause.to_string(),
+        }
+    }
+
+    pub fn foreign_endian(path: &str) -> Error {
+        Error::ForeignEndian {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Parse { path, line, cause } => write!(f, "{}:{}: {}", path, line, cause),
+            Error::Corrupt {
+                path,
+                offset,
+                cause,
+            } => write!(f, "{}: byte {}: {}", path, offset, cause),
+            Error::Invalid { path, cause } => write!(f, "{}: {}", path, cause),
+            Error::ForeignEndian { path } => write!(
+                f,
+                "{}: written on a host of the opposite byte order, which this build cannot map",
+                path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
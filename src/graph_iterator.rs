@@ -1,7 +1,12 @@
+use crate::adjacency::{Adjacency, WeightedAdjacency};
+use crate::error::{Error, Result, STREAM};
+use crate::header::{self, expect_kind, Checksum, Header, HeaderWriter, Kind, HEADER_BYTES};
 use crate::hilbert_curve::{convert_to_hilbert_and_execute, BytewiseCached};
 use crate::typedrw::TypedMemoryMap;
+use crate::validate::check_counts;
 use std::cell::Cell;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::ops::{ControlFlow, Range};
 
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum Mapper {
@@ -14,6 +19,9 @@ pub enum Mapper {
     /// Read From vertex/edge file pair
     Vertex,
 
+    /// Read From symmetric vertex/edge file pair (.binodes/.biedges)
+    SymmetricVertex,
+
     /// Read from hilbert file pair
     Hilbert,
 
@@ -23,6 +31,139 @@ pub enum Mapper {
 
 pub trait EdgeMapper {
     fn map_edges(&self, action: impl FnMut(u32, u32));
+
+    /// Presents edges to `action` as `map_edges` does, until `action` breaks.
+    ///
+    /// Returns the number of edges presented, including the one at which `action` broke. The
+    /// default walks every edge regardless, only not presenting those after a break; mappers
+    /// that can stop part way override it.
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        let mut stopped = false;
+        self.map_edges(|x, y| {
+            if !stopped {
+                presented += 1;
+                stopped = action(x, y).is_break();
+            }
+        });
+        presented
+    }
+}
+
+/// An in-memory edge list, handy for small graphs and tests.
+impl EdgeMapper for [(u32, u32)] {
+    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        for &(x, y) in self.iter() {
+            action(x, y);
+        }
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        for (index, &(x, y)) in self.iter().enumerate() {
+            if action(x, y).is_break() {
+                return index as u64 + 1;
+            }
+        }
+        self.len() as u64
+    }
+}
+
+impl EdgeMapper for Vec<(u32, u32)> {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        self[..].map_edges(action)
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self[..].map_edges_until(action)
+    }
+}
+
+/// A reference to a graph, so that adapters can wrap graphs they do not own.
+impl<G: EdgeMapper + ?Sized> EdgeMapper for &G {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        (**self).map_edges(action)
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        (**self).map_edges_until(action)
+    }
+}
+
+/// A graph whose edges carry `f32` weights, such as lengths for shortest paths.
+pub trait WeightedEdgeMapper {
+    fn map_weighted_edges(&self, action: impl FnMut(u32, u32, f32));
+}
+
+impl WeightedEdgeMapper for [(u32, u32, f32)] {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        for &(x, y, weight) in self.iter() {
+            action(x, y, weight);
+        }
+    }
+}
+
+impl WeightedEdgeMapper for Vec<(u32, u32, f32)> {
+    fn map_weighted_edges(&self, action: impl FnMut(u32, u32, f32)) {
+        self[..].map_weighted_edges(action)
+    }
+}
+
+impl<G: WeightedEdgeMapper + ?Sized> WeightedEdgeMapper for &G {
+    fn map_weighted_edges(&self, action: impl FnMut(u32, u32, f32)) {
+        (**self).map_weighted_edges(action)
+    }
+}
+
+/// A graph whose edges can be divided among threads.
+pub trait ParallelEdgeMapper: Sync {
+    /// A share of the edges, which a thread maps as any other graph.
+    type Part<'a>: EdgeMapper + Send + Sync
+    where
+        Self: 'a;
+
+    /// Divides the edges into at most `parts` disjoint shares of about equal size, which
+    /// together present every edge once.
+    fn partition(&self, parts: usize) -> Vec<Self::Part<'_>>;
+}
+
+impl ParallelEdgeMapper for [(u32, u32)] {
+    type Part<'a> = &'a [(u32, u32)];
+
+    fn partition(&self, parts: usize) -> Vec<&[(u32, u32)]> {
+        let size = self.len().div_ceil(parts.max(1)).max(1);
+        self.chunks(size).collect()
+    }
+}
+
+impl ParallelEdgeMapper for Vec<(u32, u32)> {
+    type Part<'a> = &'a [(u32, u32)];
+
+    fn partition(&self, parts: usize) -> Vec<&[(u32, u32)]> {
+        self[..].partition(parts)
+    }
+}
+
+/// Divides records, each standing for `count` edges, into at most `parts` contiguous ranges of
+/// about equal numbers of edges, returning the ranges of records and of edges.
+fn split_counts(counts: &[u32], parts: usize) -> Vec<(Range<usize>, Range<usize>)> {
+    let total: u64 = counts.iter().map(|&count| count as u64).sum();
+    let parts = parts.max(1) as u64;
+    let mut ranges = Vec::new();
+    let (mut record, mut edge) = (0, 0);
+    let mut seen = 0u64;
+    for (index, &count) in counts.iter().enumerate() {
+        seen += count as u64;
+        // close a range once it reaches its share of the edges.
+        if seen * parts >= (ranges.len() as u64 + 1) * total {
+            ranges.push((record..index + 1, edge..seen as usize));
+            record = index + 1;
+            edge = seen as usize;
+        }
+    }
+    if record < counts.len() {
+        ranges.push((record..counts.len(), edge..seen as usize));
+    }
+    ranges
 }
 
 pub struct DeltaCompressedReaderMapper<R: Read, F: Fn() -> R> {
@@ -31,15 +172,24 @@ pub struct DeltaCompressedReaderMapper<R: Read, F: Fn() -> R> {
 
 impl<R: Read, F: Fn() -> R> DeltaCompressedReaderMapper<R, F> {
     pub fn new(reader: F) -> DeltaCompressedReaderMapper<R, F> {
-        DeltaCompressedReaderMapper { reader: reader }
+        DeltaCompressedReaderMapper { reader }
     }
 }
 
 impl<R: Read, F: Fn() -> R> EdgeMapper for DeltaCompressedReaderMapper<R, F> {
     fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.map_edges_until(|x, y| {
+            action(x, y);
+            ControlFlow::Continue(())
+        });
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
         let mut hilbert = BytewiseCached::new();
         let mut current = 0u64;
-        let mut reader = (self.reader)();
+        let (_, mut reader) =
+            header::strip(STREAM, (self.reader)()).unwrap_or_else(|err| panic!("{}", err));
 
         let mut delta = 0u64; // for accumulating a delta
         let mut depth = 0u8; // for counting number of zeros
@@ -59,13 +209,17 @@ impl<R: Read, F: Fn() -> R> EdgeMapper for DeltaCompressedReaderMapper<R, F> {
                         current += delta;
                         delta = 0;
                         let (x, y) = hilbert.detangle(current);
-                        action(x, y);
+                        presented += 1;
+                        if action(x, y).is_break() {
+                            return presented;
+                        }
                     } else {
                         depth -= 1;
                     }
                 }
             }
         }
+        presented
     }
 }
 
@@ -74,13 +228,27 @@ pub struct DeltaCompressedSliceMapper<'a> {
 }
 
 impl<'a> DeltaCompressedSliceMapper<'a> {
+    /// Maps the delta-compressed stream in `slice`, skipping its header if it has one.
     pub fn new(slice: &'a [u8]) -> DeltaCompressedSliceMapper<'a> {
-        DeltaCompressedSliceMapper { slice: slice }
+        match Header::parse(STREAM, slice).unwrap_or_else(|err| panic!("{}", err)) {
+            Some(_) => DeltaCompressedSliceMapper {
+                slice: &slice[HEADER_BYTES..],
+            },
+            None => DeltaCompressedSliceMapper { slice },
+        }
     }
 }
 
 impl<'a> EdgeMapper for DeltaCompressedSliceMapper<'a> {
     fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+        self.map_edges_until(|x, y| {
+            action(x, y);
+            ControlFlow::Continue(())
+        });
+    }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
         let mut hilbert = BytewiseCached::new();
         let mut current = 0u64;
 
@@ -92,7 +260,10 @@ impl<'a> EdgeMapper for DeltaCompressedSliceMapper<'a> {
             if byte > 0 {
                 current += byte as u64;
                 let (x, y) = hilbert.detangle(current);
-                action(x, y);
+                presented += 1;
+                if action(x, y).is_break() {
+                    return presented;
+                }
             } else {
                 let mut depth = 2;
                 while unsafe { *self.slice.get_unchecked(cursor) } == 0 {
@@ -108,9 +279,13 @@ impl<'a> EdgeMapper for DeltaCompressedSliceMapper<'a> {
 
                 current += delta;
                 let (x, y) = hilbert.detangle(current);
-                action(x, y);
+                presented += 1;
+                if action(x, y).is_break() {
+                    return presented;
+                }
             }
         }
+        presented
     }
 }
 
@@ -121,17 +296,81 @@ pub struct UpperLowerMemMapper {
 
 impl UpperLowerMemMapper {
     pub fn new(graph_name: &str) -> UpperLowerMemMapper {
-        UpperLowerMemMapper {
-            upper: TypedMemoryMap::new(format!("{}.upper", graph_name)),
-            lower: TypedMemoryMap::new(format!("{}.lower", graph_name)),
+        UpperLowerMemMapper::try_new(graph_name).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(graph_name: &str) -> Result<UpperLowerMemMapper> {
+        let upper_path = format!("{}.upper", graph_name);
+        let lower_path = format!("{}.lower", graph_name);
+        let mapper = UpperLowerMemMapper {
+            upper: TypedMemoryMap::try_new(upper_path.clone())?,
+            lower: TypedMemoryMap::try_new(lower_path.clone())?,
+        };
+        expect_kind(&upper_path, mapper.upper.header(), Kind::Upper)?;
+        expect_kind(&lower_path, mapper.lower.header(), Kind::Lower)?;
+        let total = mapper.upper[..].iter().map(|&(_, c)| c as u64).sum();
+        check_counts(&upper_path, total, &lower_path, mapper.lower[..].len())?;
+        Ok(mapper)
+    }
+
+    /// The header of the `.upper` file, if it has one.
+    pub fn header(&self) -> Option<&Header> {
+        self.upper.header()
+    }
+
+    /// The number of edges, one per `.lower` record.
+    pub fn edge_count(&self) -> u64 {
+        self.lower[..].len() as u64
+    }
+}
+
+impl UpperLowerMemMapper {
+    fn slices(&self) -> UpperLowerSlices<'_> {
+        UpperLowerSlices {
+            upper: &self.upper[..],
+            lower: &self.lower[..],
         }
     }
 }
 
 impl EdgeMapper for UpperLowerMemMapper {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        self.slices().map_edges(action)
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.slices().map_edges_until(action)
+    }
+}
+
+/// Splits a Hilbert-format graph by ranges of tiles.
+impl ParallelEdgeMapper for UpperLowerMemMapper {
+    type Part<'a> = UpperLowerSlices<'a>;
+
+    fn partition(&self, parts: usize) -> Vec<UpperLowerSlices<'_>> {
+        let upper = &self.upper[..];
+        let counts: Vec<u32> = upper.iter().map(|&(_, count)| count).collect();
+        split_counts(&counts, parts)
+            .into_iter()
+            .map(|(tiles, edges)| UpperLowerSlices {
+                upper: &upper[tiles],
+                lower: &self.lower[..][edges],
+            })
+            .collect()
+    }
+}
+
+/// A run of the tiles of a Hilbert-format graph, with their edges.
+#[derive(Clone, Copy)]
+pub struct UpperLowerSlices<'a> {
+    upper: &'a [((u16, u16), u32)],
+    lower: &'a [(u16, u16)],
+}
+
+impl EdgeMapper for UpperLowerSlices<'_> {
     fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
-        let mut slice = &self.lower[..];
-        for &((u16_x, u16_y), count) in &self.upper[..] {
+        let mut slice = self.lower;
+        for &((u16_x, u16_y), count) in self.upper {
             let u16_x = (u16_x as u32) << 16;
             let u16_y = (u16_y as u32) << 16;
             for &(l16_x, l16_y) in &slice[..count as usize] {
@@ -141,26 +380,298 @@ impl EdgeMapper for UpperLowerMemMapper {
             slice = &slice[count as usize..];
         }
     }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        let mut slice = self.lower;
+        for &((u16_x, u16_y), count) in self.upper {
+            let u16_x = (u16_x as u32) << 16;
+            let u16_y = (u16_y as u32) << 16;
+            for &(l16_x, l16_y) in &slice[..count as usize] {
+                presented += 1;
+                if action(u16_x | l16_x as u32, u16_y | l16_y as u32).is_break() {
+                    return presented;
+                }
+            }
+
+            slice = &slice[count as usize..];
+        }
+        presented
+    }
+}
+
+/// A Hilbert-format graph with `<prefix>.hweights`, the weight of each edge of `.lower`.
+pub struct WeightedUpperLowerMemMapper {
+    graph: UpperLowerMemMapper,
+    weights: TypedMemoryMap<f32>,
+}
+
+impl WeightedUpperLowerMemMapper {
+    pub fn try_new(graph_name: &str) -> Result<WeightedUpperLowerMemMapper> {
+        let graph = UpperLowerMemMapper::try_new(graph_name)?;
+        let weights_path = format!("{}.hweights", graph_name);
+        let weights = TypedMemoryMap::try_new(weights_path.clone())?;
+        expect_kind(&weights_path, weights.header(), Kind::HilbertWeights)?;
+        let lower_path = format!("{}.lower", graph_name);
+        let edges = graph.lower[..].len() as u64;
+        check_counts(&lower_path, edges, &weights_path, weights[..].len())?;
+        Ok(WeightedUpperLowerMemMapper { graph, weights })
+    }
+}
+
+impl EdgeMapper for WeightedUpperLowerMemMapper {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        self.graph.map_edges(action)
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.graph.map_edges_until(action)
+    }
+}
+
+impl WeightedEdgeMapper for WeightedUpperLowerMemMapper {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        let mut weights = self.weights[..].iter();
+        self.graph
+            .map_edges(|x, y| action(x, y, *weights.next().unwrap()));
+    }
 }
 
 pub struct NodesEdgesMemMapper {
     nodes: TypedMemoryMap<(u32, u32)>,
     edges: TypedMemoryMap<u32>,
+    offsets_path: String,
+    offsets: Option<Offsets>,
+}
+
+/// An index of where each vertex's edges start, built in memory or mapped from a file.
+enum Offsets {
+    Built(Vec<u64>),
+    Mapped(TypedMemoryMap<u64>),
+}
+
+impl Offsets {
+    fn as_slice(&self) -> &[u64] {
+        match self {
+            Offsets::Built(offsets) => &offsets[..],
+            Offsets::Mapped(offsets) => &offsets[..],
+        }
+    }
 }
 
 impl NodesEdgesMemMapper {
     pub fn new(graph_name: &str) -> NodesEdgesMemMapper {
-        NodesEdgesMemMapper {
-            nodes: TypedMemoryMap::new(format!("{}.nodes", graph_name)),
-            edges: TypedMemoryMap::new(format!("{}.edges", graph_name)),
+        NodesEdgesMemMapper::try_new(graph_name).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(graph_name: &str) -> Result<NodesEdgesMemMapper> {
+        NodesEdgesMemMapper::try_from_files(
+            format!("{}.nodes", graph_name),
+            format!("{}.edges", graph_name),
+            format!("{}.offsets", graph_name),
+            (Kind::Nodes, Kind::Edges),
+        )
+    }
+
+    /// Maps the deduplicated, symmetrized `.binodes`/`.biedges` pair written by `to_vertex`.
+    pub fn new_symmetric(graph_name: &str) -> NodesEdgesMemMapper {
+        NodesEdgesMemMapper::try_new_symmetric(graph_name).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new_symmetric(graph_name: &str) -> Result<NodesEdgesMemMapper> {
+        NodesEdgesMemMapper::try_from_files(
+            format!("{}.binodes", graph_name),
+            format!("{}.biedges", graph_name),
+            format!("{}.bioffsets", graph_name),
+            (Kind::SymmetricNodes, Kind::SymmetricEdges),
+        )
+    }
+
+    fn try_from_files(
+        nodes_path: String,
+        edges_path: String,
+        offsets_path: String,
+        kinds: (Kind, Kind),
+    ) -> Result<NodesEdgesMemMapper> {
+        let mapper = NodesEdgesMemMapper {
+            nodes: TypedMemoryMap::try_new(nodes_path.clone())?,
+            edges: TypedMemoryMap::try_new(edges_path.clone())?,
+            offsets_path,
+            offsets: None,
+        };
+        expect_kind(&nodes_path, mapper.nodes.header(), kinds.0)?;
+        expect_kind(&edges_path, mapper.edges.header(), kinds.1)?;
+        let total = mapper.nodes[..].iter().map(|&(_, c)| c as u64).sum();
+        check_counts(&nodes_path, total, &edges_path, mapper.edges[..].len())?;
+        Ok(mapper)
+    }
+
+    /// The header of the `.nodes` file, if it has one.
+    pub fn header(&self) -> Option<&Header> {
+        self.nodes.header()
+    }
+
+    /// The number of edges, one per `.edges` record.
+    pub fn edge_count(&self) -> u64 {
+        self.edges[..].len() as u64
+    }
+
+    /// The path of the offset index, `<prefix>.offsets` (or `.bioffsets` if symmetric).
+    pub fn offsets_path(&self) -> &str {
+        &self.offsets_path
+    }
+
+    /// Prepares the offset index that `Adjacency` lookups need.
+    ///
+    /// The index is mapped from `offsets_path` if that file exists and was built from this
+    /// graph's `.nodes`, as its header records their number and checksum, and otherwise built
+    /// with a pass over `.nodes`, which must list each vertex at most once and in increasing
+    /// order. If `persist`, a built index is written to `offsets_path`.
+    pub fn index(&mut self, persist: bool) -> Result<()> {
+        if self.offsets.is_some() {
+            return Ok(());
+        }
+        if let Ok(mapped) = TypedMemoryMap::<u64>::try_new(self.offsets_path.clone()) {
+            let offsets = &mapped[..];
+            let header_matches = match mapped.header() {
+                Some(header) => {
+                    header.kind == Kind::Offsets
+                        && header.edges == Some(self.edges[..].len() as u64)
+                        && header.indexed == Some(self.nodes_digest())
+                }
+                None => false,
+            };
+            if header_matches && offsets.last() == Some(&(self.edges[..].len() as u64)) {
+                self.offsets = Some(Offsets::Mapped(mapped));
+                return Ok(());
+            }
+        }
+
+        let offsets = self.build_offsets()?;
+        if persist {
+            self.write_offsets(&offsets)?;
+        }
+        self.offsets = Some(Offsets::Built(offsets));
+        Ok(())
+    }
+
+    fn build_offsets(&self) -> Result<Vec<u64>> {
+        let nodes = &self.nodes[..];
+        let vertices = nodes.last().map_or(0, |&(node, _)| node as usize + 1);
+        let mut offsets = Vec::with_capacity(vertices + 1);
+        let mut offset = 0u64;
+        for (index, &(node, count)) in nodes.iter().enumerate() {
+            if offsets.len() > node as usize {
+                return Err(Error::invalid(
+                    &self.offsets_path,
+                    format!(
+                        "cannot index vertex {} at record {}, as vertices are not in increasing order",
+                        node, index
+                    ),
+                ));
+            }
+            offsets.resize(node as usize + 1, offset);
+            offset += count as u64;
+        }
+        offsets.push(offset);
+        Ok(offsets)
+    }
+
+    fn write_offsets(&self, offsets: &[u64]) -> Result<()> {
+        let path = &self.offsets_path;
+        let write = || -> std::io::Result<()> {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            let mut writer = HeaderWriter::new(file, Kind::Offsets, true)?;
+            for &offset in offsets.iter() {
+                writer.write_all(&offset.to_ne_bytes())?;
+            }
+            let vertices = offsets.len() as u64 - 1;
+            writer.describe(Some(vertices), Some(self.edges[..].len() as u64), false);
+            let (records, checksum) = self.nodes_digest();
+            writer.describe_indexed(records, checksum);
+            writer.finish().map(|_| ())
+        };
+        write().map_err(|err| Error::io(path, err))
+    }
+
+    /// The number of `.nodes` records and their checksum, which an index is built from.
+    fn nodes_digest(&self) -> (u64, u64) {
+        (
+            self.nodes[..].len() as u64,
+            Checksum::of(self.nodes.bytes()),
+        )
+    }
+
+    fn offsets(&self) -> &[u64] {
+        match &self.offsets {
+            Some(offsets) => offsets.as_slice(),
+            None => panic!("{} has not been indexed", self.offsets_path),
+        }
+    }
+}
+
+/// Random access to a vertex-format graph, once `index` has been called.
+impl Adjacency for NodesEdgesMemMapper {
+    fn nodes(&self) -> u32 {
+        (self.offsets().len() - 1) as u32
+    }
+
+    fn neighbors(&self, vertex: u32) -> &[u32] {
+        let offsets = self.offsets();
+        match offsets.get(vertex as usize + 1) {
+            Some(&end) => &self.edges[..][offsets[vertex as usize] as usize..end as usize],
+            None => &[],
+        }
+    }
+}
+
+impl NodesEdgesMemMapper {
+    fn slices(&self) -> NodesEdgesSlices<'_> {
+        NodesEdgesSlices {
+            nodes: &self.nodes[..],
+            edges: &self.edges[..],
         }
     }
 }
 
 impl EdgeMapper for NodesEdgesMemMapper {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        self.slices().map_edges(action)
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.slices().map_edges_until(action)
+    }
+}
+
+/// Splits a vertex-format graph by ranges of vertices.
+impl ParallelEdgeMapper for NodesEdgesMemMapper {
+    type Part<'a> = NodesEdgesSlices<'a>;
+
+    fn partition(&self, parts: usize) -> Vec<NodesEdgesSlices<'_>> {
+        let nodes = &self.nodes[..];
+        let counts: Vec<u32> = nodes.iter().map(|&(_, count)| count).collect();
+        split_counts(&counts, parts)
+            .into_iter()
+            .map(|(vertices, edges)| NodesEdgesSlices {
+                nodes: &nodes[vertices],
+                edges: &self.edges[..][edges],
+            })
+            .collect()
+    }
+}
+
+/// A run of the vertices of a vertex-format graph, with their edges.
+#[derive(Clone, Copy)]
+pub struct NodesEdgesSlices<'a> {
+    nodes: &'a [(u32, u32)],
+    edges: &'a [u32],
+}
+
+impl EdgeMapper for NodesEdgesSlices<'_> {
     fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
-        let mut slice = &self.edges[..];
-        for &(node, count) in &self.nodes[..] {
+        let mut slice = self.edges;
+        for &(node, count) in self.nodes {
             for &edge in &slice[..count as usize] {
                 action(node, edge);
             }
@@ -168,30 +679,200 @@ impl EdgeMapper for NodesEdgesMemMapper {
             slice = &slice[count as usize..];
         }
     }
+
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        let mut presented = 0;
+        let mut slice = self.edges;
+        for &(node, count) in self.nodes {
+            for &edge in &slice[..count as usize] {
+                presented += 1;
+                if action(node, edge).is_break() {
+                    return presented;
+                }
+            }
+
+            slice = &slice[count as usize..];
+        }
+        presented
+    }
+}
+
+/// A vertex-format graph with `<prefix>.weights`, the weight of each edge of `.edges`.
+pub struct WeightedNodesEdgesMemMapper {
+    graph: NodesEdgesMemMapper,
+    weights: TypedMemoryMap<f32>,
+}
+
+impl WeightedNodesEdgesMemMapper {
+    pub fn try_new(graph_name: &str) -> Result<WeightedNodesEdgesMemMapper> {
+        let graph = NodesEdgesMemMapper::try_new(graph_name)?;
+        let weights_path = format!("{}.weights", graph_name);
+        let weights = TypedMemoryMap::try_new(weights_path.clone())?;
+        expect_kind(&weights_path, weights.header(), Kind::Weights)?;
+        let edges_path = format!("{}.edges", graph_name);
+        let edges = graph.edges[..].len() as u64;
+        check_counts(&edges_path, edges, &weights_path, weights[..].len())?;
+        Ok(WeightedNodesEdgesMemMapper { graph, weights })
+    }
+
+    /// Prepares the offset index that `WeightedAdjacency` lookups need, as
+    /// `NodesEdgesMemMapper::index` does.
+    pub fn index(&mut self, persist: bool) -> Result<()> {
+        self.graph.index(persist)
+    }
+}
+
+impl Adjacency for WeightedNodesEdgesMemMapper {
+    fn nodes(&self) -> u32 {
+        self.graph.nodes()
+    }
+
+    fn neighbors(&self, vertex: u32) -> &[u32] {
+        self.graph.neighbors(vertex)
+    }
+}
+
+/// Random access to a weighted vertex-format graph, once `index` has been called.
+impl WeightedAdjacency for WeightedNodesEdgesMemMapper {
+    fn weights(&self, vertex: u32) -> &[f32] {
+        let offsets = self.graph.offsets();
+        match offsets.get(vertex as usize + 1) {
+            Some(&end) => &self.weights[..][offsets[vertex as usize] as usize..end as usize],
+            None => &[],
+        }
+    }
+}
+
+impl EdgeMapper for WeightedNodesEdgesMemMapper {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        self.graph.map_edges(action)
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.graph.map_edges_until(action)
+    }
+}
+
+impl WeightedEdgeMapper for WeightedNodesEdgesMemMapper {
+    fn map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) {
+        let mut weights = self.weights[..].iter();
+        self.graph
+            .map_edges(|x, y| action(x, y, *weights.next().unwrap()));
+    }
 }
 
 pub struct ReaderMapper<B: ::std::io::BufRead, F: Fn() -> B> {
     pub reader: F,
+    /// Name reported in errors, typically the path the reader was opened from.
+    pub name: String,
 }
 
 impl<B: ::std::io::BufRead, F: Fn() -> B> ReaderMapper<B, F> {
     pub fn new(reader: F) -> ReaderMapper<B, F> {
-        ReaderMapper { reader: reader }
+        ReaderMapper {
+            reader,
+            name: STREAM.to_owned(),
+        }
     }
-}
 
-impl<R: ::std::io::BufRead, RF: Fn() -> R> EdgeMapper for ReaderMapper<R, RF> {
-    fn map_edges(&self, mut action: impl FnMut(u32, u32)) {
+    pub fn with_name(mut self, name: &str) -> ReaderMapper<B, F> {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// As `map_edges`, but reports read errors and malformed lines instead of panicking.
+    pub fn try_map_edges(&self, mut action: impl FnMut(u32, u32)) -> Result<()> {
+        self.try_map_lines(false, |src, dst, _| {
+            action(src, dst);
+            ControlFlow::Continue(())
+        })
+        .map(|_| ())
+    }
+
+    /// As `map_edges_until`, but reports read errors and malformed lines instead of panicking.
+    pub fn try_map_edges_until(
+        &self,
+        mut action: impl FnMut(u32, u32) -> ControlFlow<()>,
+    ) -> Result<u64> {
+        self.try_map_lines(false, |src, dst, _| action(src, dst))
+    }
+
+    /// As `map_weighted_edges`, but reports read errors and malformed lines instead of
+    /// panicking.
+    pub fn try_map_weighted_edges(&self, mut action: impl FnMut(u32, u32, f32)) -> Result<()> {
+        self.try_map_lines(true, |src, dst, weight| {
+            action(src, dst, weight);
+            ControlFlow::Continue(())
+        })
+        .map(|_| ())
+    }
+
+    /// Parses each line as `src dst`, followed by a weight if `weighted`.
+    ///
+    /// A missing weight is one, so unweighted edge lists read as unit weights. Stops once
+    /// `action` breaks, and returns the number of edges presented.
+    fn try_map_lines(
+        &self,
+        weighted: bool,
+        mut action: impl FnMut(u32, u32, f32) -> ControlFlow<()>,
+    ) -> Result<u64> {
+        let mut presented = 0;
         let reader = (self.reader)();
-        for readline in reader.lines() {
-            let line = readline.ok().expect("read error");
+        for (index, readline) in reader.lines().enumerate() {
+            let line = readline.map_err(|err| Error::io(&self.name, err))?;
             if !line.starts_with('#') {
                 let mut elts = line[..].split_whitespace();
-                let src: u32 = elts.next().unwrap().parse().ok().expect("malformed src");
-                let dst: u32 = elts.next().unwrap().parse().ok().expect("malformed dst");
-                action(src, dst);
+                let src = self.parse_field(elts.next(), index + 1, "src")?;
+                let dst = self.parse_field(elts.next(), index + 1, "dst")?;
+                let weight = match elts.next() {
+                    Some(field) if weighted => {
+                        self.parse_field(Some(field), index + 1, "weight")?
+                    }
+                    _ => 1.0,
+                };
+                presented += 1;
+                if action(src, dst, weight).is_break() {
+                    break;
+                }
             }
         }
+        Ok(presented)
+    }
+
+    fn parse_field<T>(&self, field: Option<&str>, line: usize, what: &str) -> Result<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match field {
+            Some(text) => text.parse().map_err(|err| {
+                Error::parse(
+                    &self.name,
+                    line,
+                    format!("malformed {} {:?}: {}", what, text, err),
+                )
+            }),
+            None => Err(Error::parse(&self.name, line, format!("missing {}", what))),
+        }
+    }
+}
+
+impl<R: ::std::io::BufRead, RF: Fn() -> R> EdgeMapper for ReaderMapper<R, RF> {
+    fn map_edges(&self, action: impl FnMut(u32, u32)) {
+        self.try_map_edges(action)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    fn map_edges_until(&self, action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        self.try_map_edges_until(action)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl<R: ::std::io::BufRead, RF: Fn() -> R> WeightedEdgeMapper for ReaderMapper<R, RF> {
+    fn map_weighted_edges(&self, action: impl FnMut(u32, u32, f32)) {
+        self.try_map_weighted_edges(action)
+            .unwrap_or_else(|err| panic!("{}", err));
     }
 }
 
@@ -204,10 +885,8 @@ pub struct CachingReaderMapper<B: ::std::io::BufRead, F: Fn() -> B> {
 
 impl<B: ::std::io::BufRead, F: Fn() -> B> CachingReaderMapper<B, F> {
     pub fn new(reader: F, cap_upper: usize, cap_lower: usize) -> CachingReaderMapper<B, F> {
-        let mut upper = Vec::new();
-        upper.reserve(cap_upper);
-        let mut lower = Vec::new();
-        lower.reserve(cap_lower);
+        let upper = Vec::with_capacity(cap_upper);
+        let lower = Vec::with_capacity(cap_lower);
         CachingReaderMapper::<B, F> {
             reader: ReaderMapper::new(reader),
             upper: Cell::new(upper),
@@ -215,6 +894,12 @@ impl<B: ::std::io::BufRead, F: Fn() -> B> CachingReaderMapper<B, F> {
             is_cached: Cell::new(false),
         }
     }
+
+    /// Names the underlying reader in errors, as `ReaderMapper::with_name` does.
+    pub fn with_name(mut self, name: &str) -> CachingReaderMapper<B, F> {
+        self.reader = self.reader.with_name(name);
+        self
+    }
 }
 
 impl<B: ::std::io::BufRead, F: Fn() -> B> EdgeMapper for CachingReaderMapper<B, F> {
@@ -243,4 +928,235 @@ impl<B: ::std::io::BufRead, F: Fn() -> B> EdgeMapper for CachingReaderMapper<B,
         self.upper.set(upper);
         self.lower.set(lower);
     }
+
+    /// Stops early from the cache once it is filled. Before then the file is read directly,
+    /// as a pass that stops part way cannot fill the cache.
+    fn map_edges_until(&self, mut action: impl FnMut(u32, u32) -> ControlFlow<()>) -> u64 {
+        if !self.is_cached.get() {
+            return self.reader.map_edges_until(action);
+        }
+        let upper = self.upper.take();
+        let lower = self.lower.take();
+        let mut presented = 0;
+        let mut slice = &lower[..];
+        'tiles: for &((u16_x, u16_y), count) in upper.iter() {
+            let u16_x = (u16_x as u32) << 16;
+            let u16_y = (u16_y as u32) << 16;
+            for &(l16_x, l16_y) in &slice[0..count as usize] {
+                presented += 1;
+                if action(u16_x | l16_x as u32, u16_y | l16_y as u32).is_break() {
+                    break 'tiles;
+                }
+            }
+            slice = &slice[count as usize..];
+        }
+        self.upper.set(upper);
+        self.lower.set(lower);
+        presented
+    }
+}
+
+#[test]
+fn test_reader_reports_malformed_line() {
+    let text = "# comment\n0 1\n1 x\n";
+    let mapper = ReaderMapper::new(|| text.as_bytes()).with_name("test.el");
+    let mut edges = Vec::new();
+    match mapper.try_map_edges(|x, y| edges.push((x, y))) {
+        Err(Error::Parse { path, line, .. }) => assert_eq!((path.as_str(), line), ("test.el", 3)),
+        other => panic!("expected parse error, found {:?}", other),
+    }
+    assert_eq!(edges, vec![(0, 1)]);
+}
+
+#[test]
+fn test_vertex_offsets_index() {
+    let dir = std::env::temp_dir().join(format!("cost-offsets-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("one").to_str().unwrap().to_owned();
+    for suffix in ["nodes", "edges"] {
+        let source = format!("./sample_inputs/one.{}", suffix);
+        std::fs::copy(source, format!("{}.{}", prefix, suffix)).unwrap();
+    }
+
+    let mut streamed = vec![Vec::new(); 8];
+    NodesEdgesMemMapper::new(&prefix).map_edges(|x, y| streamed[x as usize].push(y));
+
+    let mut built = NodesEdgesMemMapper::new(&prefix);
+    built.index(true).unwrap();
+    let mut mapped = NodesEdgesMemMapper::new(&prefix);
+    mapped.index(false).unwrap();
+    assert!(matches!(mapped.offsets, Some(Offsets::Mapped(_))));
+    for graph in [&built, &mapped] {
+        for vertex in 0..8 {
+            assert_eq!(graph.neighbors(vertex), &streamed[vertex as usize][..]);
+            assert_eq!(
+                graph.out_degree(vertex),
+                streamed[vertex as usize].len() as u32
+            );
+        }
+        assert_eq!(graph.neighbors(100), &[] as &[u32]);
+    }
+
+    // another graph with as many edges, all from vertex 0, must not reuse the index.
+    let nodes: Vec<u8> = [0u32, 7].iter().flat_map(|x| x.to_ne_bytes()).collect();
+    std::fs::write(format!("{}.nodes", prefix), nodes).unwrap();
+    let mut other = NodesEdgesMemMapper::new(&prefix);
+    other.index(false).unwrap();
+    assert!(matches!(other.offsets, Some(Offsets::Built(_))));
+    assert_eq!(other.neighbors(0), &[1, 2, 3, 4, 5, 6, 7]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_weighted_formats() {
+    use crate::cli::convert::{hilbert, vertex, HilbertArgs, VertexArgs};
+
+    let dir = std::env::temp_dir().join(format!("cost-weighted-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("graph.el").to_str().unwrap().to_owned();
+    let prefix = dir.join("graph").to_str().unwrap().to_owned();
+    std::fs::write(&source, "# src dst weight\n0 1 2.5\n0 2\n1 2 0.5\n2 0 -4\n").unwrap();
+
+    vertex(VertexArgs {
+        source: source.clone(),
+        prefix: prefix.clone(),
+        weighted: true,
+        header: true,
+    })
+    .unwrap();
+    hilbert(HilbertArgs {
+        prefix: prefix.clone(),
+        dense: false,
+        weighted: true,
+        header: true,
+        budget: Default::default(),
+    })
+    .unwrap();
+
+    let expected = vec![(0, 1, 2.5), (0, 2, 1.0), (1, 2, 0.5), (2, 0, -4.0)];
+    let open = || std::io::BufReader::new(std::fs::File::open(&source).unwrap());
+    let mut vertex_graph = WeightedNodesEdgesMemMapper::try_new(&prefix).unwrap();
+    let hilbert_graph = WeightedUpperLowerMemMapper::try_new(&prefix).unwrap();
+    let mut read = Vec::new();
+    ReaderMapper::new(open).map_weighted_edges(|x, y, w| read.push((x, y, w)));
+    let mut mapped = Vec::new();
+    vertex_graph.map_weighted_edges(|x, y, w| mapped.push((x, y, w)));
+    let mut tiled = Vec::new();
+    hilbert_graph.map_weighted_edges(|x, y, w| tiled.push((x, y, w)));
+    tiled.sort_by_key(|&(x, y, _)| (x, y));
+    assert_eq!((&read, &mapped, &tiled), (&expected, &expected, &expected));
+
+    vertex_graph.index(false).unwrap();
+    assert_eq!(vertex_graph.neighbors(0), &[1, 2]);
+    assert_eq!(vertex_graph.weights(0), &[2.5, 1.0]);
+    assert_eq!(vertex_graph.weights(9), &[] as &[f32]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unsorted_vertex_conversion() {
+    use crate::algorithms::strongly_connected;
+    use crate::cli::convert::{vertex, VertexArgs};
+
+    let dir = std::env::temp_dir().join(format!("cost-unsorted-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("graph.el").to_str().unwrap().to_owned();
+    let prefix = dir.join("graph").to_str().unwrap().to_owned();
+    // vertex 4 comes first, and its edges are not adjacent.
+    std::fs::write(&source, "4 1 0.5\n1 2\n4 3 1.5\n2 4\n1 4 2\n").unwrap();
+
+    vertex(VertexArgs {
+        source,
+        prefix: prefix.clone(),
+        weighted: true,
+        header: false,
+    })
+    .unwrap();
+
+    let mut graph = WeightedNodesEdgesMemMapper::try_new(&prefix).unwrap();
+    graph.index(false).unwrap();
+    assert_eq!(graph.neighbors(1), &[2, 4]);
+    assert_eq!(graph.weights(1), &[1.0, 2.0]);
+    assert_eq!(graph.neighbors(4), &[1, 3]);
+    assert_eq!(graph.weights(4), &[0.5, 1.5]);
+
+    let mut graph = NodesEdgesMemMapper::try_new(&prefix).unwrap();
+    graph.index(false).unwrap();
+    let scc = strongly_connected(&graph, 5);
+    assert_eq!(scc.labels, vec![0, 1, 1, 3, 1]);
+    assert_eq!((scc.components, scc.largest), (3, 3));
+
+    // the same edges grouped by source are converted as they are read, to the same files.
+    let sorted = dir.join("sorted.el").to_str().unwrap().to_owned();
+    let sorted_prefix = dir.join("sorted").to_str().unwrap().to_owned();
+    std::fs::write(&sorted, "1 2\n1 4 2\n2 4\n4 1 0.5\n4 3 1.5\n").unwrap();
+    vertex(VertexArgs {
+        source: sorted,
+        prefix: sorted_prefix.clone(),
+        weighted: true,
+        header: false,
+    })
+    .unwrap();
+    for suffix in ["nodes", "edges", "weights"] {
+        let read = |prefix: &str| std::fs::read(format!("{}.{}", prefix, suffix)).unwrap();
+        assert_eq!(read(&prefix), read(&sorted_prefix), "{}", suffix);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_map_edges_until() {
+    use crate::hilbert_curve::{encode, to_hilbert};
+
+    let text = std::fs::read_to_string("./sample_inputs/one.el").unwrap();
+    let reader = ReaderMapper::new(|| text.as_bytes());
+    let mut edges = Vec::new();
+    reader.map_edges(|x, y| edges.push((x, y)));
+    let mut compressed = Vec::new();
+    let mut prev = 0;
+    to_hilbert(&edges, |next| {
+        encode(&mut compressed, next - prev);
+        prev = next;
+    });
+    let caching = CachingReaderMapper::new(|| text.as_bytes(), 0, 0);
+
+    // each mapper presents its first `stop` edges in its own order, then stops.
+    fn check<G: EdgeMapper>(graph: &G, total: usize) {
+        let mut all = Vec::new();
+        graph.map_edges(|x, y| all.push((x, y)));
+        assert_eq!(all.len(), total);
+        for stop in [1, total / 2, total] {
+            let mut some = Vec::new();
+            let presented = graph.map_edges_until(|x, y| {
+                some.push((x, y));
+                match some.len() == stop {
+                    true => ControlFlow::Break(()),
+                    false => ControlFlow::Continue(()),
+                }
+            });
+            assert_eq!((presented, &some[..]), (stop as u64, &all[..stop]));
+        }
+        assert_eq!(
+            graph.map_edges_until(|_, _| ControlFlow::Continue(())),
+            total as u64
+        );
+    }
+    let total = edges.len();
+    check(&edges, total);
+    check(&reader, total);
+    check(&NodesEdgesMemMapper::new("./sample_inputs/one"), total);
+    check(&UpperLowerMemMapper::new("./sample_inputs/one"), total);
+    check(&DeltaCompressedSliceMapper::new(&compressed), total);
+    check(&DeltaCompressedReaderMapper::new(|| &compressed[..]), total);
+    // the file is read in its own order until a full pass fills the cache.
+    let mut first = Vec::new();
+    let presented = caching.map_edges_until(|x, y| {
+        first.push((x, y));
+        ControlFlow::Break(())
+    });
+    assert_eq!((presented, &first[..]), (1, &edges[..1]));
+    assert!(!caching.is_cached.get());
+    caching.map_edges(|_, _| {});
+    assert!(caching.is_cached.get());
+    check(&caching, total);
 }
@@ -0,0 +1,390 @@
+//! An optional, versioned header for the on-disk graph formats.
+//!
+//! A header occupies the first `HEADER_BYTES` bytes of a file and records what the file holds,
+//! so tools can tell formats apart and learn the vertex count without a scan. Files that do not
+//! start with `MAGIC` are treated as legacy headerless files. The header length is a multiple of
+//! every record size, so mapped records after it stay aligned.
+//!
+//! Layout, with integers in the byte order of the host that wrote the file, as the records
+//! after the header are. The byte-order mark lets a host of the other order refuse the file:
+//!
+//! | bytes  | field                                            |
+//! |--------|--------------------------------------------------|
+//! | 0..8   | `MAGIC`                                          |
+//! | 8..10  | version                                          |
+//! | 10     | `Kind`                                           |
+//! | 11     | flags (bit 0: vertices densely renamed)          |
+//! | 12..16 | byte-order mark `0x01020304`                     |
+//! | 16..24 | vertex count, `u64::MAX` if unknown              |
+//! | 24..32 | edge count, `u64::MAX` if unknown                |
+//! | 32..40 | FNV-1a checksum of the payload, `0` if unknown   |
+//! | 40..48 | for an index, records in the file it indexes     |
+//! | 48..56 | for an index, that file's checksum, `0` if none  |
+//! | 56..64 | reserved, zero                                   |
+
+use byteorder::{ByteOrder, NativeEndian};
+use std::io::{self, Chain, Cursor, Read, Seek, SeekFrom, Write};
+
+use crate::error::{Error, Result};
+
+pub const MAGIC: [u8; 8] = *b"COSTGRPH";
+pub const VERSION: u16 = 1;
+pub const HEADER_BYTES: usize = 64;
+
+const BYTE_ORDER_MARK: u32 = 0x0102_0304;
+const FLAG_DENSE: u8 = 1;
+const UNKNOWN: u64 = u64::MAX;
+
+/// The kind of data a file holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// `(node, count)` pairs of a `.nodes` file.
+    Nodes,
+    /// Destinations of an `.edges` file.
+    Edges,
+    /// `(node, count)` pairs of a symmetric `.binodes` file.
+    SymmetricNodes,
+    /// Destinations of a symmetric `.biedges` file.
+    SymmetricEdges,
+    /// `((x, y), count)` tiles of a `.upper` file.
+    Upper,
+    /// `(x, y)` low bits of a `.lower` file.
+    Lower,
+    /// A delta-compressed Hilbert stream.
+    Compressed,
+    /// A `.meta` or `.hmeta` sidecar, a header with no payload describing a whole graph.
+    Metadata,
+    /// `u64` edge offsets of an `.offsets` index, one per vertex and one more.
+    Offsets,
+    /// `f32` edge weights of a `.weights` file, parallel to `.edges`.
+    Weights,
+    /// `f32` edge weights of a `.hweights` file, parallel to `.lower`.
+    HilbertWeights,
+    /// `u32` dense names of a `.names` file, indexed by original vertex.
+    Names,
+    /// `u32` original identifiers of an `.originals` file, indexed by dense vertex.
+    Originals,
+}
+
+impl Kind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Kind::Nodes => 1,
+            Kind::Edges => 2,
+            Kind::SymmetricNodes => 3,
+            Kind::SymmetricEdges => 4,
+            Kind::Upper => 5,
+            Kind::Lower => 6,
+            Kind::Compressed => 7,
+            Kind::Metadata => 8,
+            Kind::Offsets => 9,
+            Kind::Weights => 10,
+            Kind::HilbertWeights => 11,
+            Kind::Names => 12,
+            Kind::Originals => 13,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Kind> {
+        match byte {
+            1 => Some(Kind::Nodes),
+            2 => Some(Kind::Edges),
+            3 => Some(Kind::SymmetricNodes),
+            4 => Some(Kind::SymmetricEdges),
+            5 => Some(Kind::Upper),
+            6 => Some(Kind::Lower),
+            7 => Some(Kind::Compressed),
+            8 => Some(Kind::Metadata),
+            9 => Some(Kind::Offsets),
+            10 => Some(Kind::Weights),
+            11 => Some(Kind::HilbertWeights),
+            12 => Some(Kind::Names),
+            13 => Some(Kind::Originals),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub kind: Kind,
+    /// One more than the largest vertex identifier.
+    pub vertices: Option<u64>,
+    pub edges: Option<u64>,
+    /// Whether vertex identifiers were densely renamed (`to_hilbert --dense`).
+    pub dense: bool,
+    pub checksum: Option<u64>,
+    /// For an index, the record count and payload checksum of the file it was built from.
+    pub indexed: Option<(u64, u64)>,
+}
+
+impl Header {
+    pub fn new(kind: Kind) -> Header {
+        Header {
+            kind,
+            vertices: None,
+            edges: None,
+            dense: false,
+            checksum: None,
+            indexed: None,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_BYTES] {
+        let mut bytes = [0u8; HEADER_BYTES];
+        bytes[0..8].copy_from_slice(&MAGIC);
+        NativeEndian::write_u16(&mut bytes[8..10], VERSION);
+        bytes[10] = self.kind.to_byte();
+        bytes[11] = if self.dense { FLAG_DENSE } else { 0 };
+        NativeEndian::write_u32(&mut bytes[12..16], BYTE_ORDER_MARK);
+        NativeEndian::write_u64(&mut bytes[16..24], self.vertices.unwrap_or(UNKNOWN));
+        NativeEndian::write_u64(&mut bytes[24..32], self.edges.unwrap_or(UNKNOWN));
+        NativeEndian::write_u64(&mut bytes[32..40], self.checksum.unwrap_or(0));
+        if let Some((records, checksum)) = self.indexed {
+            NativeEndian::write_u64(&mut bytes[40..48], records);
+            NativeEndian::write_u64(&mut bytes[48..56], checksum);
+        }
+        bytes
+    }
+
+    /// Parses the header at the start of `bytes`, read from `path`.
+    ///
+    /// Returns `Ok(None)` for legacy files, which do not start with `MAGIC`.
+    pub fn parse(path: &str, bytes: &[u8]) -> Result<Option<Header>> {
+        if bytes.len() < HEADER_BYTES || bytes[0..8] != MAGIC {
+            return Ok(None);
+        }
+        match NativeEndian::read_u32(&bytes[12..16]) {
+            BYTE_ORDER_MARK => {}
+            mark if mark == BYTE_ORDER_MARK.swap_bytes() => {
+                return Err(Error::foreign_endian(path));
+            }
+            mark => {
+                return Err(Error::invalid(
+                    path,
+                    format!("unrecognised byte-order mark {:08x}", mark),
+                ));
+            }
+        }
+        let version = NativeEndian::read_u16(&bytes[8..10]);
+        if version > VERSION {
+            return Err(Error::invalid(
+                path,
+                format!("header version {} is newer than {}", version, VERSION),
+            ));
+        }
+        let kind = Kind::from_byte(bytes[10])
+            .ok_or_else(|| Error::invalid(path, format!("unknown format kind {}", bytes[10])))?;
+        let known = |value| if value == UNKNOWN { None } else { Some(value) };
+        let checksum = NativeEndian::read_u64(&bytes[32..40]);
+        let indexed = (
+            NativeEndian::read_u64(&bytes[40..48]),
+            NativeEndian::read_u64(&bytes[48..56]),
+        );
+        Ok(Some(Header {
+            kind,
+            vertices: known(NativeEndian::read_u64(&bytes[16..24])),
+            edges: known(NativeEndian::read_u64(&bytes[24..32])),
+            dense: bytes[11] & FLAG_DENSE != 0,
+            checksum: if checksum == 0 { None } else { Some(checksum) },
+            indexed: if indexed.1 == 0 { None } else { Some(indexed) },
+        }))
+    }
+}
+
+/// Fails unless `header`, if present, describes a file of `kind`.
+pub fn expect_kind(path: &str, header: Option<&Header>, kind: Kind) -> Result<()> {
+    match header {
+        Some(header) if header.kind != kind => Err(Error::invalid(
+            path,
+            format!(
+                "header describes {:?} data, expected {:?}",
+                header.kind, kind
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The payload of a stream whose header has been read, as returned by `strip`.
+pub type Payload<R> = Chain<Cursor<Vec<u8>>, R>;
+
+/// Reads a header from the front of `reader`, if it has one.
+///
+/// The returned reader yields the payload: everything after the header, or the whole stream
+/// for legacy files.
+pub fn strip<R: Read>(path: &str, mut reader: R) -> Result<(Option<Header>, Payload<R>)> {
+    let mut prefix = vec![0u8; HEADER_BYTES];
+    let mut filled = 0;
+    while filled < HEADER_BYTES {
+        match reader.read(&mut prefix[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(Error::io(path, err)),
+        }
+    }
+    prefix.truncate(filled);
+
+    let header = Header::parse(path, &prefix)?;
+    if header.is_some() {
+        prefix.clear();
+    }
+    Ok((header, Cursor::new(prefix).chain(reader)))
+}
+
+/// 64-bit FNV-1a, used for payload checksums.
+#[derive(Clone, Copy, Debug)]
+pub struct Checksum {
+    state: u64,
+}
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum {
+    pub fn new() -> Checksum {
+        Checksum {
+            state: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+
+    #[inline]
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+
+    pub fn of(bytes: &[u8]) -> u64 {
+        let mut checksum = Checksum::new();
+        checksum.update(bytes);
+        checksum.finish()
+    }
+}
+
+/// A writer that optionally prefixes its output with a header.
+///
+/// Room for the header is reserved up front and filled in by `finish`, once the payload
+/// checksum is known. When disabled it writes the payload alone, as legacy files are.
+pub struct HeaderWriter<W: Write + Seek> {
+    writer: W,
+    header: Option<Header>,
+    checksum: Checksum,
+}
+
+impl<W: Write + Seek> HeaderWriter<W> {
+    pub fn new(mut writer: W, kind: Kind, enabled: bool) -> io::Result<HeaderWriter<W>> {
+        let header = if enabled {
+            let header = Header::new(kind);
+            writer.write_all(&header.to_bytes())?;
+            Some(header)
+        } else {
+            None
+        };
+        Ok(HeaderWriter {
+            writer,
+            header,
+            checksum: Checksum::new(),
+        })
+    }
+
+    /// Records the vertex and edge counts, and whether vertices were densely renamed.
+    pub fn describe(&mut self, vertices: Option<u64>, edges: Option<u64>, dense: bool) {
+        if let Some(header) = self.header.as_mut() {
+            header.vertices = vertices;
+            header.edges = edges;
+            header.dense = dense;
+        }
+    }
+
+    /// Records the record count and payload checksum of the file an index was built from.
+    pub fn describe_indexed(&mut self, records: u64, checksum: u64) {
+        if let Some(header) = self.header.as_mut() {
+            header.indexed = Some((records, checksum));
+        }
+    }
+
+    /// Writes the completed header, if enabled, and flushes.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(mut header) = self.header.take() {
+            header.checksum = Some(self.checksum.finish());
+            self.writer.seek(SeekFrom::Start(0))?;
+            self.writer.write_all(&header.to_bytes())?;
+            self.writer.seek(SeekFrom::End(0))?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Seek> Write for HeaderWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        if self.header.is_some() {
+            self.checksum.update(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[test]
+fn test_header_round_trip() {
+    let mut writer = HeaderWriter::new(Cursor::new(Vec::new()), Kind::Edges, true).unwrap();
+    writer.write_all(&[1, 0, 0, 0, 2, 0, 0, 0]).unwrap();
+    writer.describe(Some(3), Some(2), false);
+    let bytes = writer.finish().unwrap().into_inner();
+
+    let header = Header::parse("test", &bytes).unwrap().unwrap();
+    assert_eq!(header.kind, Kind::Edges);
+    assert_eq!((header.vertices, header.edges), (Some(3), Some(2)));
+    assert_eq!(header.checksum, Some(Checksum::of(&bytes[HEADER_BYTES..])));
+
+    let (stripped, mut payload) = strip("test", &bytes[..]).unwrap();
+    let mut rest = Vec::new();
+    payload.read_to_end(&mut rest).unwrap();
+    assert_eq!(
+        (stripped, rest),
+        (Some(header), bytes[HEADER_BYTES..].to_vec())
+    );
+
+    let (legacy, mut payload) = strip("test", &bytes[HEADER_BYTES..]).unwrap();
+    let mut rest = Vec::new();
+    payload.read_to_end(&mut rest).unwrap();
+    assert_eq!((legacy, rest), (None, bytes[HEADER_BYTES..].to_vec()));
+}
+
+#[test]
+fn test_header_byte_order() {
+    let mut header = Header::new(Kind::Nodes);
+    header.vertices = Some(3);
+    let bytes = header.to_bytes();
+    assert_eq!(Header::parse("test", &bytes).unwrap(), Some(header));
+
+    // as a host of the opposite byte order would have written it.
+    let mut foreign = bytes;
+    foreign[12..16].reverse();
+    assert!(matches!(
+        Header::parse("test", &foreign),
+        Err(Error::ForeignEndian { .. })
+    ));
+
+    let mut garbled = bytes;
+    garbled[12..16].copy_from_slice(&[9, 9, 9, 9]);
+    assert!(matches!(
+        Header::parse("test", &garbled),
+        Err(Error::Invalid { .. })
+    ));
+}
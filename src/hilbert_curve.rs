@@ -1,45 +1,89 @@
-use crate::graph_iterator::EdgeMapper;
+use crate::error::{Error, Result, STREAM};
+use crate::graph_iterator::{EdgeMapper, WeightedEdgeMapper};
+use crate::names::UNNAMED;
 use byteorder::{ReadBytesExt, WriteBytesExt};
 use std::collections::HashMap;
-use std::io::{Read, Write};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[inline]
 pub fn encode<W: Write>(writer: &mut W, diff: u64) {
-    assert!(diff > 0);
+    try_encode(writer, diff).unwrap_or_else(|err| panic!("{}", err));
+}
+
+#[inline]
+pub fn try_encode<W: Write>(writer: &mut W, diff: u64) -> Result<()> {
+    if diff == 0 {
+        return Err(Error::invalid(STREAM, "cannot encode a zero delta"));
+    }
+    let write =
+        |writer: &mut W, byte: u8| writer.write_u8(byte).map_err(|err| Error::io(STREAM, err));
     for &shift in [56, 48, 40, 32, 24, 16, 8].iter() {
         if (diff >> shift) != 0 {
-            writer.write_u8(0u8).ok().expect("write error");
+            write(writer, 0u8)?;
         }
     }
     for &shift in [56, 48, 40, 32, 24, 16, 8].iter() {
         if (diff >> shift) != 0 {
-            writer
-                .write_u8((diff >> shift) as u8)
-                .ok()
-                .expect("write error");
+            write(writer, (diff >> shift) as u8)?;
         }
     }
-    writer.write_u8(diff as u8).ok().expect("write error");
+    write(writer, diff as u8)
 }
 
 #[inline]
 pub fn decode<R: Read>(reader: &mut R) -> Option<u64> {
-    if let Ok(mut read) = reader.read_u8() {
-        let mut count = 0u64;
-        while read == 0 {
-            count += 1;
-            read = reader.read_u8().unwrap();
-        }
+    try_decode(reader, 0).unwrap_or_else(|err| panic!("{}", err))
+}
 
-        let mut diff = read as u64;
-        for _ in 0..count {
-            diff = (diff << 8) + (reader.read_u8().unwrap() as u64);
+/// Decodes one delta, returning `Ok(None)` at a clean end of stream.
+///
+/// `base` is the stream offset of the delta's first byte, so that a stream ending part way
+/// through the delta is reported as corrupt at its offset in the stream, as `Decoder` does.
+#[inline]
+pub fn try_decode<R: Read>(reader: &mut R, base: u64) -> Result<Option<u64>> {
+    let mut offset = base;
+    decode_counted(reader, &mut offset)
+}
+
+#[inline]
+fn decode_counted<R: Read>(reader: &mut R, offset: &mut u64) -> Result<Option<u64>> {
+    let mut read = match reader.read_u8() {
+        Ok(byte) => byte,
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(Error::io(STREAM, err)),
+    };
+    *offset += 1;
+
+    let mut next = |offset: &mut u64| match reader.read_u8() {
+        Ok(byte) => {
+            *offset += 1;
+            Ok(byte)
         }
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => Err(Error::corrupt(
+            STREAM,
+            *offset,
+            "stream ends inside a delta",
+        )),
+        Err(err) => Err(Error::io(STREAM, err)),
+    };
+
+    let mut count = 0u64;
+    while read == 0 {
+        count += 1;
+        read = next(offset)?;
+    }
 
-        Some(diff)
-    } else {
-        None
+    let mut diff = read as u64;
+    for _ in 0..count {
+        diff = (diff << 8) + (next(offset)? as u64);
     }
+
+    Ok(Some(diff))
 }
 
 #[test]
@@ -59,16 +103,71 @@ fn test_encode_decode() {
     assert_eq!(test_vec, test_out);
 }
 
+#[test]
+fn test_decode_truncated() {
+    let mut writer = Vec::new();
+    encode(&mut writer, 1);
+    encode(&mut writer, 1 << 20);
+    writer.pop();
+
+    let mut decoder = Decoder::new(&writer[..]);
+    assert_eq!(decoder.try_next().unwrap(), Some(1));
+    match decoder.try_next() {
+        Err(Error::Corrupt { offset, .. }) => assert_eq!(offset, writer.len() as u64),
+        other => panic!("expected corrupt stream, found {:?}", other),
+    }
+
+    let mut reader = &writer[1..];
+    match try_decode(&mut reader, 1) {
+        Err(Error::Corrupt { offset, .. }) => assert_eq!(offset, writer.len() as u64),
+        other => panic!("expected corrupt stream, found {:?}", other),
+    }
+}
+
 pub struct Decoder<R: Read> {
     reader: R,
     current: u64,
+    offset: u64,
+    name: String,
 }
 
 impl<R: Read> Decoder<R> {
     pub fn new(reader: R) -> Decoder<R> {
         Decoder {
-            reader: reader,
+            reader,
             current: 0,
+            offset: 0,
+            name: STREAM.to_owned(),
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Decoder<R> {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// Number of bytes consumed from the underlying reader.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// As `next`, but reports truncated or overflowing streams instead of panicking.
+    pub fn try_next(&mut self) -> Result<Option<u64>> {
+        let start = self.offset;
+        let diff = decode_counted(&mut self.reader, &mut self.offset).map_err(|err| match err {
+            Error::Io { source, .. } => Error::io(&self.name, source),
+            Error::Corrupt { offset, cause, .. } => Error::corrupt(&self.name, offset, cause),
+            err => err,
+        })?;
+        match diff {
+            Some(diff) => match self.current.checked_add(diff) {
+                Some(current) => {
+                    self.current = current;
+                    Ok(Some(current))
+                }
+                None => Err(Error::corrupt(&self.name, start, "delta overflows u64")),
+            },
+            None => Ok(None),
         }
     }
 }
@@ -76,20 +175,14 @@ impl<R: Read> Decoder<R> {
 impl<R: Read> Iterator for Decoder<R> {
     type Item = u64;
     fn next(&mut self) -> Option<u64> {
-        if let Some(diff) = decode(&mut self.reader) {
-            assert!(self.current < self.current + diff);
-            self.current += diff;
-            Some(self.current)
-        } else {
-            None
-        }
+        self.try_next().unwrap_or_else(|err| panic!("{}", err))
     }
 }
 
-pub fn to_hilbert<I, O>(graph: &I, mut output: O) -> ()
+pub fn to_hilbert<I, O>(graph: &I, mut output: O)
 where
     I: EdgeMapper,
-    O: FnMut(u64) -> (),
+    O: FnMut(u64),
 {
     let hilbert = BytewiseHilbert::new();
     let mut buffer = Vec::new();
@@ -107,81 +200,444 @@ pub fn convert_to_hilbert_and_execute<I, F, O>(
     make_dense: bool,
     mut action: F,
     mut output: O,
-) -> ()
+) -> Vec<u32>
+where
+    I: EdgeMapper,
+    F: FnMut(u32, u32),
+    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>),
+{
+    convert_tiles(
+        |tile| {
+            graph.map_edges(|node, edge| {
+                action(node, edge);
+                tile(node, edge, ());
+            })
+        },
+        make_dense,
+        |ux, uy, c, ls, _| output(ux, uy, c, ls),
+    )
+}
+
+/// Outputs the edges of `graph` as Hilbert tiles, renaming vertices densely in order of first
+/// appearance if `make_dense`.
+///
+/// Returns the renaming, the dense name of each original vertex with `UNNAMED` for those in no
+/// edge, or an empty vector if not `make_dense`.
+pub fn convert_to_hilbert<I, O>(graph: &I, make_dense: bool, output: O) -> Vec<u32>
 where
     I: EdgeMapper,
-    F: FnMut(u32, u32) -> (),
-    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>) -> (),
+    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>),
+{
+    convert_to_hilbert_and_execute(graph, make_dense, |_src, _dst| {}, output)
+}
+
+/// As `convert_to_hilbert`, also passing each tile's edge weights in the order of its edges.
+pub fn convert_weighted_to_hilbert<I, O>(graph: &I, make_dense: bool, output: O) -> Vec<u32>
+where
+    I: WeightedEdgeMapper,
+    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>, &[f32]),
 {
-    let mut uppers: HashMap<u32, Vec<u32>> = HashMap::new();
-    let mut names = Vec::new();
-    let mut names_count = 0i32;
+    convert_tiles(|tile| graph.map_weighted_edges(tile), make_dense, output)
+}
+
+/// Groups the edges that `map` presents into Hilbert tiles, each edge carrying a payload `T`.
+///
+/// Tiles are output in Hilbert order, with their edges' low bits and payloads sorted likewise.
+/// Returns the dense renaming, as `convert_to_hilbert` does.
+fn convert_tiles<T, M, O>(map: M, make_dense: bool, mut output: O) -> Vec<u32>
+where
+    T: Copy,
+    M: FnOnce(&mut dyn FnMut(u32, u32, T)),
+    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>, &[T]),
+{
+    let mut uppers: HashMap<u32, Vec<(u32, T)>> = HashMap::new();
+    let mut renaming = Renaming::default();
     let hilbert = BytewiseHilbert::new();
 
-    graph.map_edges(|mut node, mut edge| {
-        action(node, edge);
+    map(&mut |mut node, mut edge, payload| {
         if make_dense {
-            while names.len() as u32 <= node {
-                names.push(-1i32);
-            }
-            while names.len() as u32 <= edge {
-                names.push(-1i32);
-            }
-            if names[node as usize] == -1i32 {
-                names[node as usize] = names_count;
-                names_count += 1;
-            }
-            if names[edge as usize] == -1i32 {
-                names[edge as usize] = names_count;
-                names_count += 1;
-            }
-
-            node = names[node as usize] as u32;
-            edge = names[edge as usize] as u32;
+            node = renaming.rename(node);
+            edge = renaming.rename(edge);
         }
-        let entangled = hilbert.entangle((node as u32, edge as u32));
+        let entangled = hilbert.entangle((node, edge));
         let upper = (entangled >> 32) as u32;
         let lower = entangled as u32;
 
-        uppers.entry(upper).or_insert(Vec::new()).push(lower);
+        uppers.entry(upper).or_default().push((lower, payload));
     });
 
-    let mut keys: Vec<u32> = uppers.keys().map(|x| x.clone()).collect();
+    let mut keys: Vec<u32> = uppers.keys().copied().collect();
     keys.sort();
 
     let mut temp = Vec::new();
+    let mut payloads = Vec::new();
     for &upper in keys.iter() {
         let mut lowers = uppers.remove(&upper).unwrap();
-        if lowers.len() > 0 {
+        if !lowers.is_empty() {
             let upair = hilbert.detangle((upper as u64) << 32);
             let upperx = (upair.0 >> 16) as u16;
             let uppery = (upair.1 >> 16) as u16;
             let length = lowers.len() as u32;
 
-            lowers.sort(); // TODO : Check Radix sort perf
+            lowers.sort_by_key(|&(lower, _)| lower); // TODO : Check Radix sort perf
             temp.clear();
+            payloads.clear();
 
-            for &lower in lowers.iter() {
+            for &(lower, payload) in lowers.iter() {
                 let lpair = hilbert.detangle(((upper as u64) << 32) + (lower as u64));
                 let lowerx = (lpair.0 & 65535u32) as u16;
                 let lowery = (lpair.1 & 65535u32) as u16;
                 temp.push((lowerx, lowery));
+                payloads.push(payload);
             }
 
-            output(upperx, uppery, length, &temp);
+            output(upperx, uppery, length, &temp, &payloads);
+        }
+    }
+
+    renaming.into_names()
+}
+
+/// Renames vertices densely, in order of first appearance.
+#[derive(Default)]
+struct Renaming {
+    names: Vec<i32>,
+    count: i32,
+}
+
+impl Renaming {
+    #[inline]
+    fn rename(&mut self, vertex: u32) -> u32 {
+        while self.names.len() as u32 <= vertex {
+            self.names.push(-1i32);
+        }
+        if self.names[vertex as usize] == -1i32 {
+            self.names[vertex as usize] = self.count;
+            self.count += 1;
         }
+        self.names[vertex as usize] as u32
     }
+
+    /// The dense name of each vertex, `UNNAMED` for those never renamed.
+    fn into_names(self) -> Vec<u32> {
+        self.names
+            .into_iter()
+            .map(|name| if name < 0 { UNNAMED } else { name as u32 })
+            .collect()
+    }
+}
+
+/// As `to_hilbert`, holding at most `budget` edges in memory at once.
+///
+/// Edges are buffered, and each time `budget` of them have been collected they are sorted and
+/// spilled to a run in `dir`, holding each distinct value as a delta and the number of times it
+/// occurred. The runs are then merged, repeating each value as often as it occurred, and
+/// deleted.
+pub fn to_hilbert_external<I, O>(graph: &I, budget: usize, dir: &Path, output: O) -> Result<()>
+where
+    I: EdgeMapper,
+    O: FnMut(u64),
+{
+    let hilbert = BytewiseHilbert::new();
+    sort_external(
+        |spill| graph.map_edges(|node, edge| spill(hilbert.entangle((node, edge)))),
+        budget,
+        dir,
+        output,
+    )
 }
 
-pub fn convert_to_hilbert<I, O>(graph: &I, make_dense: bool, output: O) -> ()
+/// As `convert_to_hilbert`, holding at most `budget` edges in memory at once as
+/// `to_hilbert_external` does.
+///
+/// The dense renaming, if any, takes space proportional to the largest vertex identifier.
+pub fn convert_to_hilbert_external<I, O>(
+    graph: &I,
+    make_dense: bool,
+    budget: usize,
+    dir: &Path,
+    mut output: O,
+) -> Result<Vec<u32>>
 where
     I: EdgeMapper,
-    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>) -> (),
+    O: FnMut(u16, u16, u32, &Vec<(u16, u16)>),
+{
+    let hilbert = BytewiseHilbert::new();
+    let mut renaming = Renaming::default();
+    let mut lowers = Vec::new();
+    // the high bits of the tile being collected, and its coordinates.
+    let mut tile: Option<(u32, u16, u16)> = None;
+
+    sort_external(
+        |spill| {
+            graph.map_edges(|mut node, mut edge| {
+                if make_dense {
+                    node = renaming.rename(node);
+                    edge = renaming.rename(edge);
+                }
+                spill(hilbert.entangle((node, edge)))
+            })
+        },
+        budget,
+        dir,
+        |value| {
+            let upper = (value >> 32) as u32;
+            if let Some((current, ux, uy)) = tile {
+                if current != upper {
+                    output(ux, uy, lowers.len() as u32, &lowers);
+                    lowers.clear();
+                }
+            }
+            let (x, y) = hilbert.detangle(value);
+            tile = Some((upper, (x >> 16) as u16, (y >> 16) as u16));
+            lowers.push((x as u16, y as u16));
+        },
+    )?;
+    if let Some((_, ux, uy)) = tile {
+        output(ux, uy, lowers.len() as u32, &lowers);
+    }
+    Ok(renaming.into_names())
+}
+
+/// Writes the Hilbert values of `graph`'s edges to `dir` as sorted runs of at most `budget`
+/// distinct values each, returning the paths of the runs.
+///
+/// Runs are lz4-compressed delta streams, as the `merge` subcommand reads them. The edge
+/// `(0, 0)`, whose value deltas from zero cannot encode, is left out. Should writing fail, the
+/// runs written so far are deleted.
+pub fn write_hilbert_runs<I: EdgeMapper>(
+    graph: &I,
+    budget: usize,
+    dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let hilbert = BytewiseHilbert::new();
+    let mut runs = Runs::new(dir, RunFormat::Merge);
+    let (mut rest, _) = fill_runs(
+        |spill| graph.map_edges(|node, edge| spill(hilbert.entangle((node, edge)))),
+        budget,
+        &mut runs,
+    )?;
+    if !rest.is_empty() {
+        runs.spill(&mut rest)?;
+    }
+    Ok(runs.into_paths())
+}
+
+/// Sorts the values that `map` presents, holding at most `budget` in memory at once, and
+/// outputs them in order, each as many times as it was presented.
+fn sort_external<M, O>(map: M, budget: usize, dir: &Path, mut output: O) -> Result<()>
+where
+    M: FnOnce(&mut dyn FnMut(u64)),
+    O: FnMut(u64),
+{
+    let mut runs = Runs::new(dir, RunFormat::Counted);
+    let (mut buffer, zeros) = fill_runs(map, budget, &mut runs)?;
+
+    for _ in 0..zeros {
+        output(0);
+    }
+    if runs.paths.is_empty() {
+        buffer.sort_unstable();
+        buffer.into_iter().for_each(output);
+        return Ok(());
+    }
+
+    runs.spill(&mut buffer)?;
+    drop(buffer);
+
+    merge(runs.open()?, |value, count| {
+        for _ in 0..count {
+            output(value);
+        }
+    });
+    Ok(())
+}
+
+/// Spills the values that `map` presents to `runs` each time `budget` of them are held.
+///
+/// Returns the values still held, and how many times zero was presented: deltas start from
+/// zero and cannot encode it, so it is counted apart from the runs.
+fn fill_runs<M>(map: M, budget: usize, runs: &mut Runs) -> Result<(Vec<u64>, u64)>
+where
+    M: FnOnce(&mut dyn FnMut(u64)),
 {
-    convert_to_hilbert_and_execute(graph, make_dense, |_src, _dst| {}, output);
+    let budget = budget.max(1);
+    let mut buffer = Vec::new();
+    let mut failed = None;
+    let mut zeros = 0;
+
+    map(&mut |value| {
+        if value == 0 {
+            zeros += 1;
+            return;
+        }
+        buffer.push(value);
+        if buffer.len() >= budget {
+            if failed.is_none() {
+                failed = runs.spill(&mut buffer).err();
+            }
+            buffer.clear();
+        }
+    });
+    match failed {
+        Some(err) => Err(err),
+        None => Ok((buffer, zeros)),
+    }
+}
+
+/// Distinguishes the runs of concurrent sorts within one process.
+static RUN_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// How `Runs` writes the values of a run.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RunFormat {
+    /// Deltas between distinct values, lz4-compressed, as the `merge` subcommand reads.
+    Merge,
+    /// Deltas between distinct values, each followed by how many times the value occurred.
+    Counted,
+}
+
+/// Sorted runs spilled to files in a directory, which are deleted when dropped.
+struct Runs<'a> {
+    dir: &'a Path,
+    format: RunFormat,
+    paths: Vec<PathBuf>,
+}
+
+impl<'a> Runs<'a> {
+    fn new(dir: &'a Path, format: RunFormat) -> Runs<'a> {
+        Runs {
+            dir,
+            format,
+            paths: Vec::new(),
+        }
+    }
+
+    /// The paths of the runs, which are then no longer deleted.
+    fn into_paths(mut self) -> Vec<PathBuf> {
+        mem::take(&mut self.paths)
+    }
+
+    /// Sorts `values` and writes each distinct one to a new run, as deltas, leaving `values`
+    /// empty.
+    fn spill(&mut self, values: &mut Vec<u64>) -> Result<()> {
+        values.sort_unstable();
+        let run = RUN_COUNT.fetch_add(1, Ordering::Relaxed);
+        let compress = self.format == RunFormat::Merge;
+        let counted = self.format == RunFormat::Counted;
+        let extension = if compress { ".lz4" } else { "" };
+        let path = self
+            .dir
+            .join(format!("cost-run-{}-{}{}", process::id(), run, extension));
+        let name = path.display().to_string();
+        self.paths.push(path.clone());
+
+        let write_deltas = |mut writer: &mut dyn Write| -> Result<()> {
+            let mut prev = 0u64;
+            for group in values.chunk_by(|a, b| a == b) {
+                try_encode(&mut writer, group[0] - prev)?;
+                if counted {
+                    try_encode(&mut writer, group.len() as u64)?;
+                }
+                prev = group[0];
+            }
+            Ok(())
+        };
+        let write = || -> Result<()> {
+            let file = File::create(&path).map_err(|err| Error::io(&name, err))?;
+            if compress {
+                let mut encoder = lz4::EncoderBuilder::new()
+                    .build(BufWriter::new(file))
+                    .map_err(|err| Error::io(&name, err))?;
+                write_deltas(&mut encoder)?;
+                let (mut writer, result) = encoder.finish();
+                result
+                    .and_then(|()| writer.flush())
+                    .map_err(|err| Error::io(&name, err))
+            } else {
+                let mut writer = BufWriter::new(file);
+                write_deltas(&mut writer)?;
+                writer.flush().map_err(|err| Error::io(&name, err))
+            }
+        };
+        let written = write().map_err(|err| match err {
+            Error::Io { source, .. } => Error::io(&name, source),
+            err => err,
+        });
+        values.clear();
+        written
+    }
+
+    /// Readers of the counted runs, in the order they were spilled.
+    fn open(&self) -> Result<Vec<CountedRun>> {
+        self.paths
+            .iter()
+            .map(|path| {
+                let name = path.display().to_string();
+                File::open(path)
+                    .map(|file| CountedRun {
+                        reader: BufReader::new(file),
+                        name: name.clone(),
+                        offset: 0,
+                        value: 0,
+                    })
+                    .map_err(|err| Error::io(&name, err))
+            })
+            .collect()
+    }
+}
+
+/// Reads the values of a `RunFormat::Counted` run, with how many times each occurred.
+struct CountedRun {
+    reader: BufReader<File>,
+    name: String,
+    offset: u64,
+    value: u64,
+}
+
+impl CountedRun {
+    /// As `next`, but reports truncated runs instead of panicking.
+    fn try_next(&mut self) -> Result<Option<(u64, u64)>> {
+        let name = &self.name;
+        let rename = |err| match err {
+            Error::Io { source, .. } => Error::io(name, source),
+            Error::Corrupt { offset, cause, .. } => Error::corrupt(name, offset, cause),
+            err => err,
+        };
+        let Some(delta) = decode_counted(&mut self.reader, &mut self.offset).map_err(rename)?
+        else {
+            return Ok(None);
+        };
+        let count = decode_counted(&mut self.reader, &mut self.offset)
+            .map_err(rename)?
+            .ok_or_else(|| Error::corrupt(name, self.offset, "run ends before a count"))?;
+        self.value += delta;
+        Ok(Some((self.value, count)))
+    }
+}
+
+impl Iterator for CountedRun {
+    type Item = (u64, u64);
+    fn next(&mut self) -> Option<(u64, u64)> {
+        self.try_next().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl Drop for Runs<'_> {
+    fn drop(&mut self) {
+        for path in self.paths.iter() {
+            let _ = fs::remove_file(path);
+        }
+    }
 }
 
-pub fn merge<I: Iterator<Item = u64>, O: FnMut(u64) -> ()>(mut iterators: Vec<I>, mut output: O) {
+/// Merges `iterators` of values, each strictly increasing and paired with a count, outputting
+/// the values in order with their counts. A value several iterators hold is output once for each.
+pub fn merge<I, O>(mut iterators: Vec<I>, mut output: O)
+where
+    I: Iterator<Item = (u64, u64)>,
+    O: FnMut(u64, u64),
+{
     let mut values = Vec::new();
     for iterator in iterators.iter_mut() {
         values.push(iterator.next());
@@ -192,11 +648,13 @@ pub fn merge<I: Iterator<Item = u64>, O: FnMut(u64) -> ()>(mut iterators: Vec<I>
     while !done {
         let mut arg_min = iterators.len();
         let mut val_min = 0u64;
+        let mut count_min = 0u64;
         for (index, &value) in values.iter().enumerate() {
-            if let Some(val) = value {
+            if let Some((val, count)) = value {
                 if arg_min > index || val < val_min {
                     arg_min = index;
                     val_min = val;
+                    count_min = count;
                     // done = false;
                 }
             }
@@ -204,12 +662,12 @@ pub fn merge<I: Iterator<Item = u64>, O: FnMut(u64) -> ()>(mut iterators: Vec<I>
 
         if arg_min < iterators.len() {
             values[arg_min] = iterators[arg_min].next();
-            if let Some(val) = values[arg_min] {
+            if let Some((val, _)) = values[arg_min] {
                 assert!(val > val_min);
             }
             assert!(val_old <= val_min);
             val_old = val_min;
-            output(val_min);
+            output(val_min, count_min);
         } else {
             done = true;
         }
@@ -219,6 +677,104 @@ pub fn merge<I: Iterator<Item = u64>, O: FnMut(u64) -> ()>(mut iterators: Vec<I>
     assert!(!values.iter().any(|x| x.is_some()));
 }
 
+#[test]
+fn test_external_conversion() {
+    // a few thousand edges with repeats, including the edge (0, 0) that deltas cannot encode,
+    // all of which both conversions keep.
+    let mut graph = vec![(0, 0), (70000, 3)];
+    for x in 0..3000u32 {
+        graph.push((x * 37 % 1000, x * 91 % 5000 + 10));
+    }
+    let repeats = graph[..500].to_vec();
+    graph.extend(repeats);
+    let dir = std::env::temp_dir().join(format!("cost-external-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut expected = Vec::new();
+    to_hilbert(&graph, |value| expected.push(value));
+    assert_eq!(expected.len(), graph.len());
+    let tiles = |external: bool, make_dense: bool| {
+        let mut edges = Vec::new();
+        let tile = |ux: u16, uy: u16, count: u32, ls: &Vec<(u16, u16)>| {
+            assert_eq!(count as usize, ls.len());
+            edges.extend(ls.iter().map(|&(lx, ly)| (ux, uy, lx, ly)));
+        };
+        let names = match external {
+            true => convert_to_hilbert_external(&graph, make_dense, 100, &dir, tile).unwrap(),
+            false => convert_to_hilbert(&graph, make_dense, tile),
+        };
+        (edges, names)
+    };
+
+    for budget in [1, 100, 10000] {
+        let mut values = Vec::new();
+        to_hilbert_external(&graph, budget, &dir, |value| values.push(value)).unwrap();
+        assert_eq!(values, expected);
+    }
+    assert_eq!(tiles(true, false), tiles(false, false));
+    assert_eq!(tiles(true, true), tiles(false, true));
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_chunked() {
+    use crate::cli::convert::{chunked, ChunkedArgs};
+    use crate::graph_iterator::DeltaCompressedReaderMapper;
+
+    let dir = std::env::temp_dir().join(format!("cost-chunked-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut graph = vec![(0, 0)];
+    for x in 0..5000u32 {
+        graph.push((x * 13 % 700, x * 29 % 900));
+    }
+    let text: String = graph
+        .iter()
+        .map(|(x, y)| format!("{} {}\n", x, y))
+        .collect();
+    let source = dir.join("graph.el").display().to_string();
+    fs::write(&source, text).unwrap();
+
+    // runs hold every distinct edge but (0, 0), in order.
+    let runs = write_hilbert_runs(&graph, 1000, &dir).unwrap();
+    assert!(runs.len() > 1);
+    let mut expected = Vec::new();
+    to_hilbert(&graph, |value| expected.push(value));
+    expected.dedup();
+    expected.remove(0);
+    for run in runs.iter() {
+        let file = File::open(run).unwrap();
+        let values: Vec<u64> = Decoder::new(lz4::Decoder::new(file).unwrap()).collect();
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(values
+            .iter()
+            .all(|value| expected.binary_search(value).is_ok()));
+        fs::remove_file(run).unwrap();
+    }
+
+    for header in [false, true] {
+        let output = dir.join("graph.cmp").display().to_string();
+        chunked(ChunkedArgs {
+            source: source.clone(),
+            output: output.clone(),
+            memory: 1,
+            temp_dir: Some(dir.clone()),
+            keep_runs: false,
+            header,
+        })
+        .unwrap();
+        let mut values = Vec::new();
+        let hilbert = BytewiseHilbert::new();
+        DeltaCompressedReaderMapper::new(|| File::open(&output).unwrap())
+            .map_edges(|x, y| values.push(hilbert.entangle((x, y))));
+        assert_eq!(values, expected);
+        fs::remove_file(&output).unwrap();
+    }
+    fs::remove_file(&source).unwrap();
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
 // algorithm drawn in large part from http://en.wikipedia.org/wiki/Hilbert_curve
 // bytewise implementation based on tracking cumulative rotation / mirroring.
 
@@ -229,6 +785,12 @@ pub struct BytewiseCached {
     prev_rot: (bool, bool),
 }
 
+impl Default for BytewiseCached {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BytewiseCached {
     #[inline(always)]
     pub fn detangle(&mut self, tangle: u64) -> (u32, u32) {
@@ -259,15 +821,13 @@ impl BytewiseCached {
             y_byte = 255 - y_byte;
         }
         if self.prev_rot.0 {
-            let temp = x_byte;
-            x_byte = y_byte;
-            y_byte = temp;
+            std::mem::swap(&mut x_byte, &mut y_byte);
         }
 
-        return (
+        (
             self.prev_out.0 + x_byte as u32,
             self.prev_out.1 + y_byte as u32,
-        );
+        )
     }
     pub fn new() -> BytewiseCached {
         let mut result = BytewiseCached {
@@ -278,7 +838,7 @@ impl BytewiseCached {
         };
 
         result.detangle(0); // ensures that we set the cached stuff correctly
-        return result;
+        result
     }
 }
 
@@ -288,6 +848,12 @@ pub struct BytewiseHilbert {
     rotation: Vec<u8>,       // info on rotation, keyed per self.entangle
 }
 
+impl Default for BytewiseHilbert {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BytewiseHilbert {
     pub fn new() -> BytewiseHilbert {
         let mut entangle = Vec::new();
@@ -309,11 +875,11 @@ impl BytewiseHilbert {
             }
         }
 
-        return BytewiseHilbert {
-            entangle: entangle,
-            detangle: detangle,
-            rotation: rotation,
-        };
+        BytewiseHilbert {
+            entangle,
+            detangle,
+            rotation,
+        }
     }
 
     pub fn entangle(&self, (mut x, mut y): (u32, u32)) -> u64 {
@@ -327,9 +893,7 @@ impl BytewiseHilbert {
                 + self.entangle[(((x_byte as u16) << 8) + y_byte as u16) as usize] as u64;
             let rotation = self.rotation[(((x_byte as u16) << 8) + y_byte as u16) as usize];
             if (rotation & 0x2) > 0 {
-                let temp = x;
-                x = y;
-                y = temp;
+                std::mem::swap(&mut x, &mut y);
             }
             if rotation == 12 || rotation == 6 {
                 x = 0xFFFFFFFF - x;
@@ -338,7 +902,7 @@ impl BytewiseHilbert {
         }
 
         debug_assert!(bit_entangle((init_x, init_y)) == result);
-        return result;
+        result
     }
 
     #[inline(always)]
@@ -350,13 +914,11 @@ impl BytewiseHilbert {
             let (x_byte, y_byte) = self.detangle[shifted as usize];
             let rotation = self.rotation[(((x_byte as u16) << 8) + y_byte as u16) as usize];
             if rotation == 12 || rotation == 6 {
-                result.0 = (1 << 8 * log_s) - result.0 - 1;
-                result.1 = (1 << 8 * log_s) - result.1 - 1;
+                result.0 = (1 << (8 * log_s)) - result.0 - 1;
+                result.1 = (1 << (8 * log_s)) - result.1 - 1;
             }
             if (rotation & 0x2) > 0 {
-                let temp = result.0;
-                result.0 = result.1;
-                result.1 = temp;
+                std::mem::swap(&mut result.0, &mut result.1);
             }
 
             result.0 += (x_byte as u32) << (8 * log_s);
@@ -364,7 +926,7 @@ impl BytewiseHilbert {
         }
 
         debug_assert!(bit_detangle(init_tangle) == result);
-        return result;
+        result
     }
 }
 
@@ -378,7 +940,7 @@ fn bit_entangle(mut pair: (u32, u32)) -> u64 {
         pair = bit_rotate(log_s, pair, rx, ry);
     }
 
-    return result;
+    result
 }
 
 fn bit_detangle(tangle: u64) -> (u32, u32) {
@@ -392,7 +954,7 @@ fn bit_detangle(tangle: u64) -> (u32, u32) {
         result = (result.0 + (rx << log_s), result.1 + (ry << log_s));
     }
 
-    return result;
+    result
 }
 
 fn bit_rotate(logn: usize, pair: (u32, u32), rx: u32, ry: u32) -> (u32, u32) {
@@ -4,7 +4,18 @@ extern crate byteorder;
 extern crate lz4;
 extern crate memmap;
 
+pub mod adapters;
+pub mod adjacency;
+pub mod algorithms;
+pub mod cli;
+pub mod error;
 pub mod graph_iterator;
+pub mod header;
 pub mod hilbert_curve;
+pub mod metadata;
+pub mod names;
 pub mod typedrw;
 pub mod utility;
+pub mod validate;
+
+pub use crate::error::{Error, Result};
@@ -0,0 +1,300 @@
+//! Working out how many vertices a graph has, so per-vertex state can be sized safely.
+//!
+//! The algorithms index per-vertex arrays with unchecked offsets, so a bound that is too small
+//! is undefined behaviour rather than a panic. The bound comes from, in order of preference, the
+//! graph's own header, a `<name>.meta` sidecar written by `stats` or `to_vertex` (`<name>.hmeta`
+//! for the Hilbert files, written by `to_hilbert`, whose vertices may have been renamed densely),
+//! or a scan of the edges. An explicit bound is only accepted if it covers every vertex.
+//!
+//! Recorded counts are only used if they plausibly describe the files as they are now: the
+//! edge count must match the records on disk, where that is known without a scan, and a sidecar
+//! must be no older than the files it describes. Otherwise the edges are scanned.
+
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+
+use crate::error::{Error, Result};
+use crate::graph_iterator::{
+    DeltaCompressedReaderMapper, EdgeMapper, Mapper, NodesEdgesMemMapper, ReaderMapper,
+    UpperLowerMemMapper,
+};
+use crate::header::{self, expect_kind, Header, Kind};
+
+/// Where a vertex bound was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Header,
+    Sidecar,
+    Scan,
+}
+
+/// The sidecar describing the graph `name` in `mode`.
+pub fn sidecar_path(mode: &Mapper, name: &str) -> String {
+    match mode {
+        Mapper::Hilbert => format!("{}.hmeta", name),
+        _ => format!("{}.meta", name),
+    }
+}
+
+/// Records the vertex and edge counts of the graph `name` in `mode` in its sidecar.
+pub fn write_sidecar(mode: &Mapper, name: &str, vertices: u64, edges: u64) -> Result<()> {
+    let path = sidecar_path(mode, name);
+    let mut header = Header::new(Kind::Metadata);
+    header.vertices = Some(vertices);
+    header.edges = Some(edges);
+    File::create(&path)
+        .and_then(|mut file| file.write_all(&header.to_bytes()))
+        .map_err(|err| Error::io(&path, err))
+}
+
+/// Reads the sidecar of the graph `name` in `mode`, if there is one.
+pub fn read_sidecar(mode: &Mapper, name: &str) -> Result<Option<Header>> {
+    let path = sidecar_path(mode, name);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => return Ok(None),
+    };
+    match Header::parse(&path, &bytes)? {
+        Some(header) => {
+            expect_kind(&path, Some(&header), Kind::Metadata)?;
+            Ok(Some(header))
+        }
+        None => Err(Error::invalid(
+            &path,
+            "sidecar does not start with a header",
+        )),
+    }
+}
+
+/// The files holding the graph `name` in `mode`.
+fn data_paths(mode: &Mapper, name: &str) -> Vec<String> {
+    let suffixes: &[&str] = match mode {
+        Mapper::Reader | Mapper::Hybrid | Mapper::Compressed => return vec![name.to_owned()],
+        Mapper::Vertex => &["nodes", "edges"],
+        Mapper::SymmetricVertex => &["binodes", "biedges"],
+        Mapper::Hilbert => &["upper", "lower"],
+    };
+    suffixes
+        .iter()
+        .map(|suffix| format!("{}.{}", name, suffix))
+        .collect()
+}
+
+/// Whether the file at `path` was last modified no earlier than any of `sources`.
+fn is_fresh(path: &str, sources: &[String]) -> bool {
+    let modified = |path: &str| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    match modified(path) {
+        Some(written) => sources
+            .iter()
+            .all(|source| modified(source).is_some_and(|modified| modified <= written)),
+        None => false,
+    }
+}
+
+/// The vertex count recorded for the graph `name` in `mode`, without scanning its edges.
+///
+/// A header or sidecar whose edge count differs from the records on disk is ignored, as is a
+/// sidecar older than the graph's files.
+pub fn recorded_vertices(mode: &Mapper, name: &str) -> Result<Option<(u64, Source)>> {
+    let (header, edges) = match mode {
+        Mapper::Reader | Mapper::Hybrid => (None, None),
+        Mapper::Vertex => {
+            let graph = NodesEdgesMemMapper::try_new(name)?;
+            (graph.header().cloned(), Some(graph.edge_count()))
+        }
+        Mapper::SymmetricVertex => {
+            let graph = NodesEdgesMemMapper::try_new_symmetric(name)?;
+            (graph.header().cloned(), Some(graph.edge_count()))
+        }
+        Mapper::Hilbert => {
+            let graph = UpperLowerMemMapper::try_new(name)?;
+            (graph.header().cloned(), Some(graph.edge_count()))
+        }
+        Mapper::Compressed => {
+            // the header was written with the stream it describes, and counting its edges
+            // would take a scan.
+            let file = File::open(name).map_err(|err| Error::io(name, err))?;
+            (header::strip(name, file)?.0, None)
+        }
+    };
+    let matches = |recorded: &Header| edges.is_none_or(|edges| recorded.edges == Some(edges));
+
+    if let Some(vertices) = header.filter(matches).and_then(|header| header.vertices) {
+        return Ok(Some((vertices, Source::Header)));
+    }
+    if !is_fresh(&sidecar_path(mode, name), &data_paths(mode, name)) {
+        return Ok(None);
+    }
+    Ok(read_sidecar(mode, name)?
+        .filter(matches)
+        .and_then(|header| header.vertices)
+        .map(|vertices| (vertices, Source::Sidecar)))
+}
+
+/// The largest vertex identifier of `graph`, as source or destination.
+pub fn max_vertex<G: EdgeMapper>(graph: &G) -> Option<u32> {
+    let mut max = None;
+    graph.map_edges(|x, y| max = max.max(Some(x.max(y))));
+    max
+}
+
+/// Scans the edges of the graph `name` in `mode` for its vertex count.
+pub fn scan_vertices(mode: &Mapper, name: &str) -> Result<u64> {
+    let max = match mode {
+        Mapper::Reader | Mapper::Hybrid => {
+            File::open(name).map_err(|err| Error::io(name, err))?;
+            let reader =
+                ReaderMapper::new(|| BufReader::new(File::open(name).unwrap())).with_name(name);
+            let mut max = None;
+            reader.try_map_edges(|x, y| max = max.max(Some(x.max(y))))?;
+            max
+        }
+        Mapper::Vertex => max_vertex(&NodesEdgesMemMapper::try_new(name)?),
+        Mapper::SymmetricVertex => max_vertex(&NodesEdgesMemMapper::try_new_symmetric(name)?),
+        Mapper::Hilbert => max_vertex(&UpperLowerMemMapper::try_new(name)?),
+        Mapper::Compressed => {
+            File::open(name).map_err(|err| Error::io(name, err))?;
+            max_vertex(&DeltaCompressedReaderMapper::new(|| {
+                BufReader::new(File::open(name).unwrap())
+            }))
+        }
+    };
+    Ok(max.map_or(0, |max| max as u64 + 1))
+}
+
+/// Determines the number of vertices to allocate state for, checking an `explicit` bound.
+///
+/// Without an explicit bound the recorded count is used, falling back to a scan. An explicit
+/// bound is checked against the recorded count, or a scan if there is none, and rejected if
+/// some vertex identifier is not below it.
+pub fn resolve_nodes(mode: &Mapper, name: &str, explicit: Option<u32>) -> Result<(u32, Source)> {
+    let (vertices, source) = match recorded_vertices(mode, name)? {
+        Some(recorded) => recorded,
+        None => (scan_vertices(mode, name)?, Source::Scan),
+    };
+    if vertices > u32::MAX as u64 {
+        return Err(Error::invalid(
+            name,
+            format!("{} vertices do not fit in u32 identifiers", vertices),
+        ));
+    }
+    match explicit {
+        Some(nodes) if (nodes as u64) < vertices => Err(Error::invalid(
+            name,
+            format!(
+                "nodes ({}) must exceed the largest vertex identifier ({}, from {:?})",
+                nodes,
+                vertices - 1,
+                source
+            ),
+        )),
+        Some(nodes) => Ok((nodes, source)),
+        None => Ok((vertices as u32, source)),
+    }
+}
+
+#[test]
+fn test_resolve_nodes() {
+    let name = "./sample_inputs/one";
+    assert_eq!(
+        resolve_nodes(&Mapper::Vertex, name, None).unwrap(),
+        (8, Source::Scan)
+    );
+    assert_eq!(
+        resolve_nodes(&Mapper::Hilbert, name, Some(10)).unwrap(),
+        (10, Source::Scan)
+    );
+    assert!(resolve_nodes(&Mapper::SymmetricVertex, name, Some(7)).is_err());
+}
+
+#[test]
+fn test_stale_sidecar() {
+    let dir = std::env::temp_dir().join(format!("cost-sidecar-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let name = dir.join("one").to_string_lossy().into_owned();
+    for suffix in ["nodes", "edges"] {
+        fs::copy(
+            format!("./sample_inputs/one.{}", suffix),
+            format!("{}.{}", name, suffix),
+        )
+        .unwrap();
+    }
+
+    write_sidecar(&Mapper::Vertex, &name, 12, 7).unwrap();
+    assert_eq!(
+        resolve_nodes(&Mapper::Vertex, &name, None).unwrap(),
+        (12, Source::Sidecar)
+    );
+
+    // a sidecar for a graph with a different number of edges.
+    write_sidecar(&Mapper::Vertex, &name, 4, 6).unwrap();
+    assert_eq!(
+        resolve_nodes(&Mapper::Vertex, &name, None).unwrap(),
+        (8, Source::Scan)
+    );
+
+    // a sidecar written before the graph was.
+    write_sidecar(&Mapper::Vertex, &name, 4, 7).unwrap();
+    File::options()
+        .write(true)
+        .open(sidecar_path(&Mapper::Vertex, &name))
+        .and_then(|file| file.set_modified(std::time::SystemTime::UNIX_EPOCH))
+        .unwrap();
+    assert_eq!(
+        resolve_nodes(&Mapper::Vertex, &name, None).unwrap(),
+        (8, Source::Scan)
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_dense_sidecar() {
+    use crate::algorithms::label_propagation;
+    use crate::cli::cc;
+    use crate::cli::convert::{hilbert, vertex, HilbertArgs, VertexArgs};
+    use clap::Parser;
+
+    let dir = std::env::temp_dir().join(format!("cost-dense-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("graph.el").to_string_lossy().into_owned();
+    let name = dir.join("graph").to_string_lossy().into_owned();
+    fs::write(&source, "10 4\n4 7\n7 12\n").unwrap();
+
+    // without headers, the counts can only come from the sidecars.
+    vertex(VertexArgs {
+        source,
+        prefix: name.clone(),
+        weighted: false,
+        header: false,
+    })
+    .unwrap();
+    hilbert(HilbertArgs {
+        prefix: name.clone(),
+        dense: true,
+        weighted: false,
+        header: false,
+        budget: Default::default(),
+    })
+    .unwrap();
+    assert_eq!(
+        resolve_nodes(&Mapper::Vertex, &name, None).unwrap(),
+        (13, Source::Sidecar)
+    );
+    assert_eq!(
+        resolve_nodes(&Mapper::Hilbert, &name, None).unwrap(),
+        (4, Source::Sidecar)
+    );
+
+    let args = cc::Args::try_parse_from(["cc", "-m", "hilbert", "-f", &name, "--original-ids"]);
+    let graph_args = args.unwrap().graph;
+    let nodes = graph_args.nodes().unwrap();
+    let names = graph_args.names(nodes).unwrap();
+    let components = label_propagation(&UpperLowerMemMapper::try_new(&name).unwrap(), nodes);
+    assert_eq!(components.components, 1);
+    let mut vertices: Vec<u32> = (0..nodes).map(|vertex| names.name(vertex)).collect();
+    vertices.sort();
+    assert_eq!(vertices, vec![4, 7, 10, 12]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,116 @@
+//! The dense renaming of vertices that `to_hilbert --dense` performs.
+//!
+//! Renaming numbers vertices in order of first appearance, so that identifiers are small and
+//! contiguous. The renaming is kept in two files of `u32`s in host byte order: `<prefix>.names`
+//! holds the dense name of each original vertex, `UNNAMED` for those in no edge, and
+//! `<prefix>.originals` holds the original identifier of each dense vertex.
+
+use crate::error::{Error, Result};
+use crate::header::{expect_kind, Kind};
+use crate::typedrw::TypedMemoryMap;
+
+/// The dense name of an original vertex that appears in no edge.
+pub const UNNAMED: u32 = u32::MAX;
+
+pub fn names_path(prefix: &str) -> String {
+    format!("{}.names", prefix)
+}
+
+pub fn originals_path(prefix: &str) -> String {
+    format!("{}.originals", prefix)
+}
+
+/// The original identifier of each dense vertex, given the dense name of each original vertex.
+pub fn invert(names: &[u32]) -> Vec<u32> {
+    let named = names.iter().filter(|&&name| name != UNNAMED).count();
+    let mut originals = vec![UNNAMED; named];
+    for (original, &name) in names.iter().enumerate() {
+        if name != UNNAMED {
+            originals[name as usize] = original as u32;
+        }
+    }
+    originals
+}
+
+/// Translates vertices of a densely renamed graph back to their original identifiers, or
+/// leaves them as they are.
+pub struct Names {
+    originals: Option<TypedMemoryMap<u32>>,
+}
+
+impl Names {
+    /// Leaves vertices as they are.
+    pub fn identity() -> Names {
+        Names { originals: None }
+    }
+
+    /// Reads `<prefix>.originals`, failing unless it names each of vertices `0 .. nodes`.
+    pub fn try_new(prefix: &str, nodes: u32) -> Result<Names> {
+        let path = originals_path(prefix);
+        let originals = TypedMemoryMap::<u32>::try_new(path.clone())?;
+        expect_kind(&path, originals.header(), Kind::Originals)?;
+        if (nodes as usize) > originals[..].len() {
+            return Err(Error::invalid(
+                &path,
+                format!(
+                    "names {} vertices, fewer than nodes ({})",
+                    originals[..].len(),
+                    nodes
+                ),
+            ));
+        }
+        Ok(Names {
+            originals: Some(originals),
+        })
+    }
+
+    /// The original identifier of `vertex`.
+    pub fn name(&self, vertex: u32) -> u32 {
+        match &self.originals {
+            Some(originals) => originals[..][vertex as usize],
+            None => vertex,
+        }
+    }
+}
+
+#[test]
+fn test_names() {
+    use std::fs;
+
+    use crate::hilbert_curve::convert_to_hilbert;
+
+    let graph = vec![(10, 4), (4, 7), (7, 10), (12, 4)];
+    let names = convert_to_hilbert(&graph, true, |_, _, _, _| {});
+    assert_eq!(names.len(), 13);
+    assert_eq!(
+        &names[4..],
+        &[1, UNNAMED, UNNAMED, 2, UNNAMED, UNNAMED, 0, UNNAMED, 3]
+    );
+    assert!(convert_to_hilbert(&graph, false, |_, _, _, _| {}).is_empty());
+    let originals = invert(&names);
+    assert_eq!(originals, vec![10, 4, 7, 12]);
+
+    let dir = std::env::temp_dir().join(format!("cost-names-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("graph").to_string_lossy().into_owned();
+    let bytes: Vec<u8> = originals.iter().flat_map(|id| id.to_ne_bytes()).collect();
+    fs::write(originals_path(&prefix), bytes).unwrap();
+
+    let renamed = Names::try_new(&prefix, 4).unwrap();
+    let mut edges = Vec::new();
+    convert_to_hilbert(&graph, true, |ux, uy, _, ls| {
+        for &(lx, ly) in ls.iter() {
+            let x = ((ux as u32) << 16) | lx as u32;
+            let y = ((uy as u32) << 16) | ly as u32;
+            edges.push((renamed.name(x), renamed.name(y)));
+        }
+    });
+    edges.sort();
+    let mut expected = graph.clone();
+    expected.sort();
+    assert_eq!(edges, expected);
+    assert!(Names::try_new(&prefix, 5).is_err());
+    assert_eq!(Names::identity().name(7), 7);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -6,22 +6,75 @@ use std::slice;
 
 use memmap;
 
+use crate::error::{Error, Result};
+use crate::header::{Header, HEADER_BYTES};
+
 pub struct TypedMemoryMap<T: Copy> {
     map: memmap::Mmap,
-    len: usize, // in bytes (needed because map extends to full block)
+    offset: usize, // in bytes, past any header
+    len: usize,    // in records (needed because map extends to full block)
+    header: Option<Header>,
     phn: PhantomData<T>,
 }
 
 impl<T: Copy> TypedMemoryMap<T> {
     pub fn new(filename: String) -> TypedMemoryMap<T> {
-        let file = File::open(filename).ok().expect("error opening file");
-        let size = file.metadata().ok().expect("error reading metadata").len() as usize;
+        TypedMemoryMap::try_new(filename).unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        TypedMemoryMap {
-            map: unsafe { memmap::Mmap::map(&file).unwrap() },
-            len: size / mem::size_of::<T>(),
-            phn: PhantomData,
+    /// Maps `filename`, failing if its length is not a whole number of `T` records.
+    pub fn try_new(filename: String) -> Result<TypedMemoryMap<T>> {
+        let (map, trailing) = TypedMemoryMap::try_new_truncated(filename.clone())?;
+        if trailing > 0 {
+            return Err(Error::invalid(
+                &filename,
+                format!(
+                    "{} trailing bytes after the last {}-byte record",
+                    trailing,
+                    mem::size_of::<T>()
+                ),
+            ));
+        }
+        Ok(map)
+    }
+
+    /// Maps the whole records of `filename`, also returning the number of bytes left over.
+    ///
+    /// Records start after the file's header, if it has one.
+    pub fn try_new_truncated(filename: String) -> Result<(TypedMemoryMap<T>, usize)> {
+        let file = File::open(&filename).map_err(|err| Error::io(&filename, err))?;
+        let size = file
+            .metadata()
+            .map_err(|err| Error::io(&filename, err))?
+            .len() as usize;
+
+        // empty files cannot be mapped, but stand for perfectly good empty graphs.
+        let map = if size == 0 {
+            memmap::MmapMut::map_anon(1).and_then(|map| map.make_read_only())
+        } else {
+            unsafe { memmap::Mmap::map(&file) }
         }
+        .map_err(|err| Error::io(&filename, err))?;
+        let header = Header::parse(&filename, &map[..])?;
+        let offset = if header.is_some() { HEADER_BYTES } else { 0 };
+
+        let map = TypedMemoryMap {
+            map,
+            offset,
+            len: (size - offset) / mem::size_of::<T>(),
+            header,
+            phn: PhantomData,
+        };
+        Ok((map, (size - offset) % mem::size_of::<T>()))
+    }
+
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    /// The bytes of the mapped records, excluding any header.
+    pub fn bytes(&self) -> &[u8] {
+        &self.map[self.offset..self.offset + self.len * mem::size_of::<T>()]
     }
 }
 
@@ -29,6 +82,6 @@ impl<T: Copy> ops::Index<ops::RangeFull> for TypedMemoryMap<T> {
     type Output = [T];
     #[inline]
     fn index(&self, _index: ops::RangeFull) -> &[T] {
-        unsafe { slice::from_raw_parts(self.map.as_ptr() as *const T, self.len) }
+        unsafe { slice::from_raw_parts(self.map.as_ptr().add(self.offset) as *const T, self.len) }
     }
 }
@@ -13,3 +13,124 @@ macro_rules! const_switch_bool {
         }
     };
 }
+
+/// Opens the graph `$name` as `$mode` directs and evaluates `$body` with `$graph` bound to a
+/// reference to the mapper, yielding a `COST::Result` of the body's value.
+///
+/// Mappers have generic methods and so cannot be boxed; instead `$body` is instantiated once
+/// for each mapper type.
+#[macro_export]
+macro_rules! with_graph {
+    ($mode: expr, $name: expr, |$graph: ident| $body: expr) => {{
+        use $crate::graph_iterator::{
+            CachingReaderMapper, DeltaCompressedReaderMapper, Mapper, NodesEdgesMemMapper,
+            ReaderMapper, UpperLowerMemMapper,
+        };
+        let name: &str = $name;
+        let open = || ::std::io::BufReader::new(::std::fs::File::open(name).unwrap());
+        match $mode {
+            Mapper::Reader => $crate::utility::file_len(name).map(|_| {
+                let $graph = &ReaderMapper::new(open).with_name(name);
+                $body
+            }),
+            Mapper::Hybrid => $crate::utility::file_len(name).map(|len| {
+                let ulen = (len >> 2) + 1;
+                let llen = (len >> 1) + 1;
+                let $graph =
+                    &CachingReaderMapper::new(open, ulen as usize, llen as usize).with_name(name);
+                $body
+            }),
+            Mapper::Vertex => NodesEdgesMemMapper::try_new(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            Mapper::SymmetricVertex => NodesEdgesMemMapper::try_new_symmetric(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            Mapper::Hilbert => UpperLowerMemMapper::try_new(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            Mapper::Compressed => $crate::utility::file_len(name).map(|_| {
+                let $graph = &DeltaCompressedReaderMapper::new(open);
+                $body
+            }),
+        }
+    }};
+}
+
+/// As `with_graph!`, for the modes whose files carry edge weights: a text edge list with a third
+/// column in `reader` mode, `<prefix>.weights` in `vertex` mode and `<prefix>.hweights` in
+/// `hilbert` mode. Other modes are refused.
+#[macro_export]
+macro_rules! with_weighted_graph {
+    ($mode: expr, $name: expr, |$graph: ident| $body: expr) => {{
+        use $crate::graph_iterator::{
+            Mapper, ReaderMapper, WeightedNodesEdgesMemMapper, WeightedUpperLowerMemMapper,
+        };
+        let name: &str = $name;
+        let open = || ::std::io::BufReader::new(::std::fs::File::open(name).unwrap());
+        match $mode {
+            Mapper::Reader => $crate::utility::file_len(name).map(|_| {
+                let $graph = &ReaderMapper::new(open).with_name(name);
+                $body
+            }),
+            Mapper::Vertex => WeightedNodesEdgesMemMapper::try_new(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            Mapper::Hilbert => WeightedUpperLowerMemMapper::try_new(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            ref mode => Err($crate::Error::invalid(
+                name,
+                format!(
+                    "{:?} mode has no weights; use reader, vertex or hilbert",
+                    mode
+                ),
+            )),
+        }
+    }};
+}
+
+/// As `with_graph!`, for the modes whose files can be divided among threads, binding `$graph`
+/// to a `ParallelEdgeMapper`: `vertex` and `symmetric-vertex` by ranges of vertices, and
+/// `hilbert` by ranges of tiles. Other modes are refused.
+#[macro_export]
+macro_rules! with_parallel_graph {
+    ($mode: expr, $name: expr, |$graph: ident| $body: expr) => {{
+        use $crate::graph_iterator::{Mapper, NodesEdgesMemMapper, UpperLowerMemMapper};
+        let name: &str = $name;
+        match $mode {
+            Mapper::Vertex => NodesEdgesMemMapper::try_new(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            Mapper::SymmetricVertex => NodesEdgesMemMapper::try_new_symmetric(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            Mapper::Hilbert => UpperLowerMemMapper::try_new(name).map(|graph| {
+                let $graph = &graph;
+                $body
+            }),
+            ref mode => Err($crate::Error::invalid(
+                name,
+                format!(
+                    "{:?} mode cannot be divided among threads; use vertex, symmetric-vertex or hilbert",
+                    mode
+                ),
+            )),
+        }
+    }};
+}
+
+/// The length of the file at `path`, which also checks that it can be opened.
+pub fn file_len(path: &str) -> crate::Result<u64> {
+    std::fs::File::open(path)
+        .and_then(|file| file.metadata())
+        .map(|metadata| metadata.len())
+        .map_err(|err| crate::Error::io(path, err))
+}
@@ -0,0 +1,460 @@
+//! Up-front consistency checks for the on-disk graph formats.
+//!
+//! The mappers trust their inputs and index with unchecked offsets, so a truncated or
+//! mismatched file pair shows up as garbage results or a panic part way through a run. The
+//! functions here walk a graph once and report every problem they find instead.
+
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::error::{Error, Result};
+use crate::graph_iterator::{Mapper, ReaderMapper};
+use crate::header::{expect_kind, Checksum, Kind, HEADER_BYTES};
+use crate::hilbert_curve::{BytewiseHilbert, Decoder};
+use crate::typedrw::TypedMemoryMap;
+
+/// Problems beyond this many are counted but not recorded.
+pub const MAX_PROBLEMS: usize = 32;
+
+/// The outcome of validating a graph.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Number of edge records that could be read.
+    pub edges: u64,
+    /// Largest vertex identifier seen, as source or destination.
+    pub max_vertex: Option<u32>,
+    /// The first `MAX_PROBLEMS` problems found.
+    pub problems: Vec<Error>,
+    /// Problems found beyond the first `MAX_PROBLEMS`.
+    pub suppressed: u64,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn problem(&mut self, error: Error) {
+        if self.problems.len() < MAX_PROBLEMS {
+            self.problems.push(error);
+        } else {
+            self.suppressed += 1;
+        }
+    }
+
+    fn vertex(&mut self, vertex: u32, bound: Option<u32>, path: &str, offset: u64) {
+        self.max_vertex = Some(self.max_vertex.map_or(vertex, |max| max.max(vertex)));
+        if let Some(bound) = bound {
+            if vertex >= bound {
+                self.problem(Error::corrupt(path, offset, out_of_bounds(vertex, bound)));
+            }
+        }
+    }
+}
+
+/// Checks that per-vertex or per-tile counts in `counts_path` account for every record of
+/// `records_path`.
+pub(crate) fn check_counts(
+    counts_path: &str,
+    total: u64,
+    records_path: &str,
+    records: usize,
+) -> Result<()> {
+    if total != records as u64 {
+        Err(Error::invalid(
+            counts_path,
+            format!(
+                "counts sum to {} but {} holds {} records",
+                total, records_path, records
+            ),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates the graph `name` as it would be read in `mode`, optionally requiring every vertex
+/// identifier to be less than `bound`.
+pub fn validate(mode: &Mapper, name: &str, bound: Option<u32>) -> Report {
+    match mode {
+        Mapper::Reader | Mapper::Hybrid => validate_reader(name, bound),
+        Mapper::Vertex => validate_vertex(name, false, bound),
+        Mapper::SymmetricVertex => validate_vertex(name, true, bound),
+        Mapper::Hilbert => validate_hilbert(name, bound),
+        Mapper::Compressed => validate_compressed(name, bound),
+    }
+}
+
+/// Validates a textual edge list, stopping at the first malformed line.
+pub fn validate_reader(name: &str, bound: Option<u32>) -> Report {
+    let mut report = Report::default();
+    if let Err(err) = File::open(name) {
+        report.problem(Error::io(name, err));
+        return report;
+    }
+
+    let reader = ReaderMapper::new(|| BufReader::new(File::open(name).unwrap())).with_name(name);
+    let result = reader.try_map_edges(|src, dst| {
+        for vertex in [src, dst] {
+            report.max_vertex = Some(report.max_vertex.map_or(vertex, |max| max.max(vertex)));
+            if let Some(bound) = bound.filter(|&bound| vertex >= bound) {
+                let cause = format!("edge {}: {}", report.edges, out_of_bounds(vertex, bound));
+                report.problem(Error::invalid(name, cause));
+            }
+        }
+        report.edges += 1;
+    });
+    if let Err(err) = result {
+        report.problem(err);
+    }
+    report
+}
+
+/// Validates a `.nodes`/`.edges` pair, or the `.binodes`/`.biedges` pair if `symmetric`.
+pub fn validate_vertex(name: &str, symmetric: bool, bound: Option<u32>) -> Report {
+    let mut report = Report::default();
+    let (nodes_suffix, edges_suffix, kinds) = if symmetric {
+        (
+            "binodes",
+            "biedges",
+            (Kind::SymmetricNodes, Kind::SymmetricEdges),
+        )
+    } else {
+        ("nodes", "edges", (Kind::Nodes, Kind::Edges))
+    };
+    let nodes_path = format!("{}.{}", name, nodes_suffix);
+    let edges_path = format!("{}.{}", name, edges_suffix);
+    let (Some(nodes), Some(edges)) = (
+        map_records::<(u32, u32)>(&nodes_path, &mut report),
+        map_records::<u32>(&edges_path, &mut report),
+    ) else {
+        return report;
+    };
+
+    let total = nodes[..].iter().map(|&(_, count)| count as u64).sum();
+    if let Err(err) = check_counts(&nodes_path, total, &edges_path, edges[..].len()) {
+        report.problem(err);
+    }
+
+    let (nodes_base, edges_base) = (base_offset(&nodes), base_offset(&edges));
+    let mut cursor = 0;
+    for (index, &(node, count)) in nodes[..].iter().enumerate() {
+        report.vertex(node, bound, &nodes_path, nodes_base + 8 * index as u64);
+        for &edge in edges[..].iter().skip(cursor).take(count as usize) {
+            report.vertex(edge, bound, &edges_path, edges_base + 4 * cursor as u64);
+            cursor += 1;
+        }
+    }
+    report.edges = cursor as u64;
+    check_header(&mut report, &nodes_path, &nodes, kinds.0);
+    check_header(&mut report, &edges_path, &edges, kinds.1);
+    report
+}
+
+/// Validates a `.upper`/`.lower` pair, including that edges appear in Hilbert order.
+pub fn validate_hilbert(name: &str, bound: Option<u32>) -> Report {
+    let mut report = Report::default();
+    let upper_path = format!("{}.upper", name);
+    let lower_path = format!("{}.lower", name);
+    let (Some(upper), Some(lower)) = (
+        map_records::<((u16, u16), u32)>(&upper_path, &mut report),
+        map_records::<(u16, u16)>(&lower_path, &mut report),
+    ) else {
+        return report;
+    };
+
+    let total = upper[..].iter().map(|&(_, count)| count as u64).sum();
+    if let Err(err) = check_counts(&upper_path, total, &lower_path, lower[..].len()) {
+        report.problem(err);
+    }
+
+    let hilbert = BytewiseHilbert::new();
+    let base = base_offset(&lower);
+    let mut previous = None;
+    let mut cursor = 0;
+    for &((u16_x, u16_y), count) in upper[..].iter() {
+        let u16_x = (u16_x as u32) << 16;
+        let u16_y = (u16_y as u32) << 16;
+        for &(l16_x, l16_y) in lower[..].iter().skip(cursor).take(count as usize) {
+            let (x, y) = (u16_x | l16_x as u32, u16_y | l16_y as u32);
+            let offset = base + 4 * cursor as u64;
+            report.vertex(x, bound, &lower_path, offset);
+            report.vertex(y, bound, &lower_path, offset);
+
+            let tangle = hilbert.entangle((x, y));
+            if previous.is_some_and(|previous| previous > tangle) {
+                report.problem(Error::corrupt(
+                    &lower_path,
+                    offset,
+                    format!("edge ({}, {}) is out of Hilbert order", x, y),
+                ));
+            }
+            previous = Some(tangle);
+            cursor += 1;
+        }
+    }
+    report.edges = cursor as u64;
+    check_header(&mut report, &upper_path, &upper, Kind::Upper);
+    check_header(&mut report, &lower_path, &lower, Kind::Lower);
+    report
+}
+
+/// Validates a delta-compressed Hilbert stream.
+pub fn validate_compressed(name: &str, bound: Option<u32>) -> Report {
+    let mut report = Report::default();
+    let Some(stream) = map_records::<u8>(name, &mut report) else {
+        return report;
+    };
+
+    let hilbert = BytewiseHilbert::new();
+    let base = base_offset(&stream);
+    let mut decoder = Decoder::new(stream.bytes()).with_name(name);
+    loop {
+        let offset = base + decoder.offset();
+        match decoder.try_next() {
+            Ok(Some(tangle)) => {
+                let (x, y) = hilbert.detangle(tangle);
+                report.vertex(x, bound, name, offset);
+                report.vertex(y, bound, name, offset);
+                report.edges += 1;
+            }
+            Ok(None) => break,
+            Err(err) => {
+                report.problem(match err {
+                    Error::Corrupt { offset, cause, .. } => {
+                        Error::corrupt(name, base + offset, cause)
+                    }
+                    err => err,
+                });
+                break;
+            }
+        }
+    }
+    check_header(&mut report, name, &stream, Kind::Compressed);
+    report
+}
+
+/// Checks a file's header, if any, against its kind, the edges read and its payload.
+fn check_header<T: Copy>(report: &mut Report, path: &str, map: &TypedMemoryMap<T>, kind: Kind) {
+    let Some(header) = map.header() else {
+        return;
+    };
+    if let Err(err) = expect_kind(path, Some(header), kind) {
+        report.problem(err);
+    }
+    if let Some(edges) = header.edges.filter(|&edges| edges != report.edges) {
+        let cause = format!(
+            "header records {} edges but {} were read",
+            edges, report.edges
+        );
+        report.problem(Error::invalid(path, cause));
+    }
+    if let (Some(vertices), Some(max)) = (header.vertices, report.max_vertex) {
+        if vertices <= max as u64 {
+            let cause = format!(
+                "header records {} vertices but vertex {} occurs",
+                vertices, max
+            );
+            report.problem(Error::invalid(path, cause));
+        }
+    }
+    if let Some(checksum) = header.checksum {
+        let actual = Checksum::of(map.bytes());
+        if checksum != actual {
+            let cause = format!(
+                "checksum {:016x} does not match header {:016x}",
+                actual, checksum
+            );
+            report.problem(Error::invalid(path, cause));
+        }
+    }
+}
+
+/// Byte offset of the first record, past any header.
+fn base_offset<T: Copy>(map: &TypedMemoryMap<T>) -> u64 {
+    if map.header().is_some() {
+        HEADER_BYTES as u64
+    } else {
+        0
+    }
+}
+
+fn out_of_bounds(vertex: u32, bound: u32) -> String {
+    format!("vertex {} is not below the bound {}", vertex, bound)
+}
+
+fn map_records<T: Copy>(path: &str, report: &mut Report) -> Option<TypedMemoryMap<T>> {
+    match TypedMemoryMap::try_new_truncated(path.to_owned()) {
+        Ok((map, trailing)) => {
+            if trailing > 0 {
+                report.problem(Error::invalid(
+                    path,
+                    format!(
+                        "{} trailing bytes after the last {}-byte record",
+                        trailing,
+                        std::mem::size_of::<T>()
+                    ),
+                ));
+            }
+            Some(map)
+        }
+        Err(err) => {
+            report.problem(err);
+            None
+        }
+    }
+}
+
+#[test]
+fn test_validate_sample_inputs() {
+    for mode in [Mapper::Vertex, Mapper::SymmetricVertex, Mapper::Hilbert] {
+        let report = validate(&mode, "./sample_inputs/one", Some(8));
+        assert!(report.is_ok(), "{:?}: {:?}", mode, report.problems);
+        assert_eq!(report.max_vertex, Some(7));
+    }
+
+    let report = validate(&Mapper::Vertex, "./sample_inputs/one", Some(7));
+    assert_eq!(report.problems.len(), 1);
+}
+
+/// Copies `sample_inputs/one.<suffix>` into `dir`, passing its bytes through `edit` first.
+#[cfg(test)]
+fn copy_sample(dir: &std::path::Path, suffix: &str, edit: impl FnOnce(&mut Vec<u8>)) {
+    let mut bytes = std::fs::read(format!("./sample_inputs/one.{}", suffix)).unwrap();
+    edit(&mut bytes);
+    std::fs::write(dir.join(format!("one.{}", suffix)), bytes).unwrap();
+}
+
+/// Whether `report` holds an `Error::Invalid` for a path ending in `suffix` mentioning `text`.
+#[cfg(test)]
+fn has_invalid(report: &Report, suffix: &str, text: &str) -> bool {
+    report.problems.iter().any(|problem| {
+        matches!(problem, Error::Invalid { path, cause }
+            if path.ends_with(suffix) && cause.contains(text))
+    })
+}
+
+#[test]
+fn test_validate_trailing_bytes() {
+    let dir = std::env::temp_dir().join(format!("cost-fsck-trailing-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("one").to_string_lossy().into_owned();
+    copy_sample(&dir, "nodes", |_| {});
+    copy_sample(&dir, "edges", |bytes| bytes.extend_from_slice(&[0, 0]));
+
+    let report = validate_vertex(&prefix, false, None);
+    assert_eq!(report.problems.len(), 1, "{:?}", report.problems);
+    assert!(has_invalid(&report, ".edges", "2 trailing bytes"));
+    assert_eq!(report.edges, 7);
+
+    let edges = format!("{}.edges", prefix);
+    assert!(matches!(
+        TypedMemoryMap::<u32>::try_new(edges),
+        Err(Error::Invalid { .. })
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_degree_sum() {
+    let dir = std::env::temp_dir().join(format!("cost-fsck-degrees-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("one").to_string_lossy().into_owned();
+    // the first vertex claims two edges, where `.edges` has room for its one.
+    copy_sample(&dir, "nodes", |bytes| bytes[4] = 2);
+    copy_sample(&dir, "edges", |_| {});
+
+    let report = validate_vertex(&prefix, false, None);
+    assert_eq!(report.problems.len(), 1, "{:?}", report.problems);
+    assert!(has_invalid(&report, ".nodes", "counts sum to 8"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_truncated_hilbert() {
+    let dir = std::env::temp_dir().join(format!("cost-fsck-hilbert-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("one").to_string_lossy().into_owned();
+
+    // a whole `.lower` record missing.
+    copy_sample(&dir, "upper", |_| {});
+    copy_sample(&dir, "lower", |bytes| bytes.truncate(bytes.len() - 4));
+    let report = validate_hilbert(&prefix, None);
+    assert_eq!(report.problems.len(), 1, "{:?}", report.problems);
+    assert!(has_invalid(&report, ".upper", "counts sum to 7"));
+    assert_eq!(report.edges, 6);
+
+    // `.lower` cut part way through a record.
+    copy_sample(&dir, "lower", |bytes| bytes.truncate(bytes.len() - 2));
+    let report = validate_hilbert(&prefix, None);
+    assert_eq!(report.problems.len(), 2, "{:?}", report.problems);
+    assert!(has_invalid(&report, ".lower", "2 trailing bytes"));
+    assert!(has_invalid(&report, ".upper", "counts sum to 7"));
+
+    // `.upper` cut part way through its only tile.
+    copy_sample(&dir, "upper", |bytes| bytes.truncate(4));
+    copy_sample(&dir, "lower", |_| {});
+    let report = validate_hilbert(&prefix, None);
+    assert_eq!(report.problems.len(), 2, "{:?}", report.problems);
+    assert!(has_invalid(&report, ".upper", "4 trailing bytes"));
+    assert!(has_invalid(&report, ".upper", "counts sum to 0"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_header_counts() {
+    use crate::header::HeaderWriter;
+    use std::io::Write;
+
+    let dir = std::env::temp_dir().join(format!("cost-fsck-header-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("one").to_string_lossy().into_owned();
+    copy_sample(&dir, "nodes", |_| {});
+    let edges = format!("{}.edges", prefix);
+    let payload = std::fs::read("./sample_inputs/one.edges").unwrap();
+    let mut writer = HeaderWriter::new(File::create(&edges).unwrap(), Kind::Edges, true).unwrap();
+    writer.write_all(&payload).unwrap();
+    writer.describe(Some(5), Some(6), false);
+    writer.finish().unwrap();
+
+    let report = validate_vertex(&prefix, false, None);
+    assert_eq!(report.problems.len(), 2, "{:?}", report.problems);
+    assert!(has_invalid(&report, ".edges", "header records 6 edges"));
+    assert!(has_invalid(&report, ".edges", "header records 5 vertices"));
+
+    // the same edge count and ids, but a payload the checksum does not describe.
+    let mut bytes = std::fs::read(&edges).unwrap();
+    bytes.swap(HEADER_BYTES, HEADER_BYTES + 4);
+    std::fs::write(&edges, bytes).unwrap();
+    let report = validate_vertex(&prefix, false, None);
+    assert!(has_invalid(&report, ".edges", "does not match header"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_truncated_stream() {
+    use crate::hilbert_curve::encode;
+
+    let dir = std::env::temp_dir().join(format!("cost-fsck-stream-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("one.compressed").to_string_lossy().into_owned();
+    let mut bytes = Vec::new();
+    encode(&mut bytes, 1);
+    encode(&mut bytes, 1 << 20);
+    bytes.pop();
+    std::fs::write(&path, &bytes).unwrap();
+
+    let report = validate_compressed(&path, None);
+    assert_eq!(report.problems.len(), 1, "{:?}", report.problems);
+    match &report.problems[0] {
+        Error::Corrupt { offset, .. } => assert_eq!(*offset, bytes.len() as u64),
+        other => panic!("expected a corrupt stream, found {:?}", other),
+    }
+    assert_eq!(report.edges, 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
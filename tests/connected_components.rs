@@ -1,29 +1,46 @@
 use std::process::{Command, Stdio};
 
-static TESTS: [(&str, &str); 1] = [(
-    "-f ./sample_inputs/one.el -n 8",
-    "./ok/connected_components-one.el-8.ok",
-)];
-
-static MODES: [&str; 2] = ["reader", "hybrid"];
+static TESTS: [(&str, &str, &[&str]); 3] = [
+    (
+        "-f ./sample_inputs/one.el -n 8",
+        "./ok/connected_components-one.el-8.ok",
+        &["reader", "hybrid"],
+    ),
+    (
+        "-f ./sample_inputs/one -n 8",
+        "./ok/connected_components-one.el-8.ok",
+        &["vertex", "symmetric-vertex", "hilbert"],
+    ),
+    (
+        "-f ./sample_inputs/empty.el",
+        "./ok/connected_components-empty.el.ok",
+        &["reader", "hybrid"],
+    ),
+];
 
 static BUILDS: [&str; 2] = ["dev", "release"];
 
-fn run_test_expected_output(opt: String, input: String, ok_file: String) -> Result<(), Vec<u8>> {
+/// Binaries computing connected components, with any subcommand they need.
+static COMMANDS: [(&str, &[&str]); 3] = [
+    ("connected_components", &[]),
+    ("cost", &["cc"]),
+    ("cost", &["uf", "--labels"]),
+];
+
+fn run_test_expected_output(
+    opt: String,
+    (binary, subcommand): (&str, &[&str]),
+    input: String,
+    ok_file: String,
+) -> Result<(), Vec<u8>> {
     let input_vec: Vec<String> = input
         .clone()
         .split_whitespace()
         .map(|s| s.to_owned())
         .collect();
     let test_out = Command::new("cargo")
-        .args([
-            "run",
-            "--profile",
-            &opt.clone(),
-            "--bin",
-            "connected_components",
-            "--",
-        ])
+        .args(["run", "--profile", &opt.clone(), "--bin", binary, "--"])
+        .args(subcommand)
         .args(input_vec)
         .stdout(Stdio::piped())
         .spawn()
@@ -36,6 +53,7 @@ fn run_test_expected_output(opt: String, input: String, ok_file: String) -> Resu
         .stdout
         .expect("Failed to open stdout of connected_components");
 
+    #[allow(clippy::expect_fun_call)]
     let checker_out = Command::new("diff")
         .args(["-w", "-", &ok_file.clone()])
         .stdin(Stdio::from(test_out))
@@ -51,21 +69,24 @@ fn run_test_expected_output(opt: String, input: String, ok_file: String) -> Resu
 
 #[test]
 pub fn connected_component_tests() {
-    for (input, ok_file) in TESTS {
-        for mode in MODES {
+    for (input, ok_file, modes) in TESTS {
+        for mode in modes {
             for build in BUILDS {
-                match run_test_expected_output(
-                    build.to_owned(),
-                    input.to_owned() + " --mode " + mode,
-                    ok_file.to_owned(),
-                ) {
-                    Ok(()) => {}
-                    Err(vec) => {
-                        eprintln!("{}", std::str::from_utf8(&vec).unwrap());
-                        panic!(
-                            "Failed test with input: {}, and ok_file: {} in build {}",
-                            input, ok_file, build
-                        );
+                for command in COMMANDS {
+                    match run_test_expected_output(
+                        build.to_owned(),
+                        command,
+                        input.to_owned() + " --mode " + mode,
+                        ok_file.to_owned(),
+                    ) {
+                        Ok(()) => {}
+                        Err(vec) => {
+                            eprintln!("{}", std::str::from_utf8(&vec).unwrap());
+                            panic!(
+                                "Failed test with input: {}, and ok_file: {} in build {} of {}",
+                                input, ok_file, build, command.0
+                            );
+                        }
                     }
                 }
             }